@@ -5,22 +5,19 @@ use llmhub::api::client::Client;
 use llmhub::api::message::{Message, Role};
 use llmhub::api::request::{ApiRequest, RequestOptions};
 use llmhub::api::session::Session;
+use llmhub::api::stream::StreamAccumulator;
 use llmhub::models::models::Model;
 use rustyline::error::ReadlineError;
 use rustyline::DefaultEditor;
-use std::env;
 use std::io::{stdout, Write};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenv().ok();
 
-    let api_key = env::var("API_KEY").expect("API_KEY must be set");
-    let client = Client::new(api_key);
-    let options = RequestOptions {
-        temperature: Some(0.7),
-        ..Default::default()
-    };
+    let model = Model::Deepseek(llmhub::models::models::DEEPSEEK::R1Siliconflow);
+    let client = Client::from_env(model.provider())?;
+    let options = RequestOptions::builder().temperature(0.7).build()?;
 
     let mut session = Session::new();
     let mut rl = DefaultEditor::new()?;
@@ -37,28 +34,27 @@ async fn main() -> Result<()> {
 
                 session.add_message(Message::new(Role::User, line));
 
-                let stream_request = ApiRequest::new(
-                    Model::Deepseek(llmhub::models::models::DEEPSEEK::R1Siliconflow),
-                    Some(&session),
-                )
-                .with_options(options.clone())
-                .stream(true);
+                let stream_request = ApiRequest::new(model.clone(), Some(&session))
+                    .with_options(options.clone())
+                    .stream(true);
 
                 print!(">> Assistant: ");
                 stdout().flush()?;
 
-                let mut full_response = String::new();
+                let mut accumulator = StreamAccumulator::new();
 
                 match client.chat_stream(&stream_request) {
                     Ok(mut stream) => {
                         while let Some(chunk_result) = stream.next().await {
                             match chunk_result {
                                 Ok(chunk) => {
-                                    for choice in chunk.choices {
+                                    accumulator.push(&chunk);
+                                    // Only the first candidate is rendered live; with
+                                    // `n > 1` the others are printed once complete.
+                                    for choice in chunk.choices.iter().filter(|c| c.index == 0) {
                                         if let Some(content) = &choice.delta.content {
                                             print!("{}", content);
                                             stdout().flush()?;
-                                            full_response.push_str(content);
                                         }
                                         if let Some(reasoning) = &choice.delta.reasoning_content {
                                             print!("{}", reasoning);
@@ -78,6 +74,12 @@ async fn main() -> Result<()> {
                     }
                 }
                 println!(); // Add a newline after the response
+                if accumulator.choice_count() > 1 {
+                    for (index, content) in accumulator.contents().skip(1) {
+                        println!(">> Assistant [candidate {}]: {}", index, content);
+                    }
+                }
+                let full_response = accumulator.content(0).unwrap_or_default().to_string();
                 session.add_message(Message::new(Role::Assistant, full_response));
             }
             Err(ReadlineError::Interrupted) => {
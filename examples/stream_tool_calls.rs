@@ -0,0 +1,50 @@
+use anyhow::Result;
+use dotenv::dotenv;
+use futures::StreamExt;
+use llmhub::api::client::Client;
+use llmhub::api::message::Message;
+use llmhub::api::request::ApiRequest;
+use llmhub::api::stream::ToolCallAccumulator;
+use llmhub::api::tool::Tool;
+use llmhub::models::models::{CHATGPT, Model};
+use serde_json::{Value, json};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+
+    let model = Model::ChatGPT(CHATGPT::V4oMini);
+    let client = Client::from_env(model.provider())?;
+    let weather = Tool::function(
+        "get_weather",
+        "Get the current weather for a city",
+        json!({
+            "type": "object",
+            "properties": { "city": { "type": "string" } },
+            "required": ["city"],
+        }),
+    );
+    let request = ApiRequest::new(model, None)
+        .add_message(Message::user("What's the weather in Paris and in Tokyo?"))
+        .with_tools(vec![weather])
+        .stream(true);
+
+    let mut accumulator = ToolCallAccumulator::new();
+    let mut stream = client.chat_stream(&request)?;
+    while let Some(chunk) = stream.next().await {
+        for choice in &chunk? {
+            if let Some(tool_calls) = &choice.delta.tool_calls {
+                accumulator.push(tool_calls);
+            }
+        }
+    }
+
+    for call in accumulator.finish() {
+        let function = call.function.as_ref();
+        let name = function.and_then(|f| f.name.as_deref()).unwrap_or_default();
+        let arguments: Value = call.parse_arguments()?;
+        println!("{} ({:?}): {}", name, call.id, arguments);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,622 @@
+//! Translation between the OpenAI-style request/response types and
+//! Anthropic's native Messages API.
+
+use crate::api::message::{Content, ContentPart, Message, Role};
+use crate::api::request::ApiRequest;
+use crate::api::response::{
+    ApiChoice, ApiResponse, PromptTokensDetails, StreamChoice, StreamChunk, StreamDelta, ToolCall,
+    ToolCallFunction, Usage,
+};
+use crate::utils::error::{LlmHubError, Result};
+use futures::stream::BoxStream;
+use futures::{Stream, StreamExt};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+
+/// The `anthropic-version` header sent with every request.
+pub const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// `max_tokens` sent when neither the request nor the model's metadata
+/// provides one, since Anthropic requires it.
+pub const DEFAULT_MAX_TOKENS: u32 = 4096;
+
+/// Builds a Messages API body from a chat request.
+///
+/// System messages are lifted into the top-level `system` field, assistant
+/// tool calls become `tool_use` blocks and tool replies become `tool_result`
+/// blocks in a user turn. A `reasoning_effort` becomes a `thinking` budget
+/// unless `thinking` is set explicitly, and `stop` is sent as
+/// `stop_sequences`. Without an explicit cap, `max_tokens` is raised above
+/// the thinking budget as Anthropic requires.
+///
+/// `response_format` has no Messages API equivalent and is ignored; ask for
+/// JSON in the prompt or use a tool's input schema instead.
+pub fn request_body(request: &ApiRequest) -> Value {
+    let (system, messages) = request.split_system();
+    let options = &request.options;
+    let thinking = options.thinking.clone().or_else(|| {
+        options
+            .reasoning_effort
+            .map(|effort| json!({ "type": "enabled", "budget_tokens": effort.budget_tokens() }))
+    });
+    // Thinking counts against `max_tokens`, which must exceed the budget, so
+    // a default cap leaves room for the answer on top of it.
+    let budget = thinking
+        .as_ref()
+        .and_then(|thinking| thinking["budget_tokens"].as_u64())
+        .and_then(|budget| u32::try_from(budget).ok());
+    let max_tokens = options
+        .max_completion_tokens
+        .or(options.max_tokens)
+        .unwrap_or_else(|| {
+            let default = request
+                .model
+                .max_output_tokens()
+                .unwrap_or(DEFAULT_MAX_TOKENS);
+            budget.map_or(default, |budget| {
+                default.max(budget.saturating_add(DEFAULT_MAX_TOKENS))
+            })
+        });
+
+    let mut body = json!({
+        "model": request.model,
+        "messages": convert_messages(&messages),
+        "max_tokens": max_tokens,
+    });
+    let fields = body.as_object_mut().expect("body is an object");
+    if let Some(system) = system {
+        fields.insert("system".into(), json!(system));
+    }
+    if let Some(temperature) = options.temperature {
+        fields.insert("temperature".into(), json!(temperature));
+    }
+    if let Some(top_p) = options.top_p {
+        fields.insert("top_p".into(), json!(top_p));
+    }
+    if let Some(top_k) = options.top_k {
+        fields.insert("top_k".into(), json!(top_k));
+    }
+    if let Some(stop) = &options.stop {
+        fields.insert("stop_sequences".into(), json!(stop));
+    }
+    if let Some(stream) = options.stream {
+        fields.insert("stream".into(), json!(stream));
+    }
+    if let Some(thinking) = thinking {
+        fields.insert("thinking".into(), thinking);
+    }
+    if let Some(user) = &options.user {
+        fields.insert("metadata".into(), json!({ "user_id": user }));
+    }
+    if let Some(Value::Array(tools)) = &options.tools {
+        let tools: Vec<Value> = tools
+            .iter()
+            .filter_map(|tool| tool.get("function"))
+            .map(|function| {
+                json!({
+                    "name": function.get("name"),
+                    "description": function.get("description"),
+                    "input_schema": function.get("parameters"),
+                })
+            })
+            .collect();
+        fields.insert("tools".into(), json!(tools));
+    }
+    if let Some(tool_choice) = options.tool_choice.as_ref().and_then(convert_tool_choice) {
+        fields.insert("tool_choice".into(), tool_choice);
+    }
+    body
+}
+
+fn convert_messages(messages: &[Message]) -> Vec<Value> {
+    let mut converted: Vec<Value> = Vec::new();
+    for message in messages {
+        match message.role {
+            Role::Tool => {
+                let block = json!({
+                    "type": "tool_result",
+                    "tool_use_id": message.tool_call_id,
+                    "content": message.text().unwrap_or_default(),
+                });
+                // Results for parallel calls must share one user turn.
+                if let Some(blocks) = converted
+                    .last_mut()
+                    .filter(|last| last["role"] == "user")
+                    .and_then(|last| last["content"].as_array_mut())
+                    .filter(|blocks| blocks.iter().all(|b| b["type"] == "tool_result"))
+                {
+                    blocks.push(block);
+                } else {
+                    converted.push(json!({ "role": "user", "content": [block] }));
+                }
+            }
+            Role::Assistant if message.tool_calls.is_some() => {
+                let mut blocks = Vec::new();
+                if let Some(text) = message.text().filter(|t| !t.is_empty()) {
+                    blocks.push(json!({ "type": "text", "text": text }));
+                }
+                for call in message.tool_calls.iter().flatten() {
+                    let function = call.function.as_ref();
+                    let input = function
+                        .and_then(|f| f.arguments.as_deref())
+                        .and_then(|args| serde_json::from_str::<Value>(args).ok())
+                        .unwrap_or_else(|| json!({}));
+                    blocks.push(json!({
+                        "type": "tool_use",
+                        "id": call.id,
+                        "name": function.and_then(|f| f.name.clone()),
+                        "input": input,
+                    }));
+                }
+                converted.push(json!({ "role": "assistant", "content": blocks }));
+            }
+            _ => {
+                let role = if message.role == Role::Assistant {
+                    "assistant"
+                } else {
+                    "user"
+                };
+                converted.push(json!({
+                    "role": role,
+                    "content": convert_content(message.content.as_ref()),
+                }));
+            }
+        }
+    }
+    converted
+}
+
+/// Converts message content, turning image parts into `image` blocks with a
+/// `base64` source for `data:` URLs and a `url` source otherwise.
+fn convert_content(content: Option<&Content>) -> Value {
+    let parts = match content {
+        None => return json!(""),
+        Some(Content::Text(text)) => return json!(text),
+        Some(Content::Parts(parts)) => parts,
+    };
+    let blocks: Vec<Value> = parts
+        .iter()
+        .map(|part| match part {
+            ContentPart::Text { text } => json!({ "type": "text", "text": text }),
+            ContentPart::ImageUrl { image_url } => {
+                let source = image_url
+                    .url
+                    .strip_prefix("data:")
+                    .and_then(|rest| rest.split_once(";base64,"))
+                    .map_or_else(
+                        || json!({ "type": "url", "url": image_url.url }),
+                        |(media_type, data)| {
+                            json!({ "type": "base64", "media_type": media_type, "data": data })
+                        },
+                    );
+                json!({ "type": "image", "source": source })
+            }
+        })
+        .collect();
+    json!(blocks)
+}
+
+fn convert_tool_choice(choice: &Value) -> Option<Value> {
+    match choice {
+        Value::String(mode) => match mode.as_str() {
+            "auto" => Some(json!({ "type": "auto" })),
+            "required" => Some(json!({ "type": "any" })),
+            "none" => Some(json!({ "type": "none" })),
+            _ => None,
+        },
+        Value::Object(_) => {
+            let name = choice.pointer("/function/name")?;
+            Some(json!({ "type": "tool", "name": name }))
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum ContentBlock {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: Value,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct AnthropicUsage {
+    #[serde(default)]
+    input_tokens: Option<u32>,
+    #[serde(default)]
+    output_tokens: Option<u32>,
+    #[serde(default)]
+    cache_read_input_tokens: Option<u32>,
+}
+
+impl AnthropicUsage {
+    fn into_usage(self) -> Usage {
+        Usage {
+            prompt_tokens: self.input_tokens,
+            completion_tokens: self.output_tokens,
+            total_tokens: self
+                .input_tokens
+                .zip(self.output_tokens)
+                .map(|(input, output)| input + output),
+            prompt_tokens_details: self
+                .cache_read_input_tokens
+                .map(|cached_tokens| PromptTokensDetails { cached_tokens }),
+            ..Usage::default()
+        }
+    }
+}
+
+/// A non-streaming Messages API response.
+#[derive(Debug, Deserialize)]
+pub(crate) struct MessagesResponse {
+    id: String,
+    model: String,
+    content: Vec<ContentBlock>,
+    stop_reason: Option<String>,
+    #[serde(default)]
+    usage: Option<AnthropicUsage>,
+}
+
+impl MessagesResponse {
+    /// Converts the response into the OpenAI-style shape with one choice.
+    /// Text blocks are concatenated; thinking and other blocks are dropped.
+    pub(crate) fn into_api_response(self) -> ApiResponse {
+        let mut text = String::new();
+        let mut tool_calls = Vec::new();
+        for block in self.content {
+            match block {
+                ContentBlock::Text { text: part } => text.push_str(&part),
+                ContentBlock::ToolUse { id, name, input } => tool_calls.push(ToolCall {
+                    index: None,
+                    id: Some(id),
+                    tool_type: Some("function".to_string()),
+                    function: Some(ToolCallFunction {
+                        name: Some(name),
+                        arguments: Some(input.to_string()),
+                    }),
+                }),
+                ContentBlock::Other => {}
+            }
+        }
+        let message = Message {
+            role: Role::Assistant,
+            content: (!text.is_empty() || tool_calls.is_empty()).then_some(Content::Text(text)),
+            tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+            tool_call_id: None,
+        };
+        ApiResponse {
+            id: self.id,
+            object: "chat.completion".to_string(),
+            created: unix_now(),
+            model: self.model,
+            choices: vec![ApiChoice {
+                index: 0,
+                message,
+                finish_reason: self.stop_reason,
+                logprobs: None,
+                safety_ratings: None,
+            }],
+            usage: self.usage.map(AnthropicUsage::into_usage),
+            system_fingerprint: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum BlockDelta {
+    TextDelta {
+        text: String,
+    },
+    ThinkingDelta {
+        thinking: String,
+    },
+    InputJsonDelta {
+        partial_json: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct MessageStart {
+    id: String,
+    model: String,
+    #[serde(default)]
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct MessageDelta {
+    stop_reason: Option<String>,
+}
+
+/// A server-sent event of a streaming Messages API response.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum StreamEvent {
+    MessageStart {
+        message: MessageStart,
+    },
+    ContentBlockStart {
+        index: usize,
+        content_block: ContentBlock,
+    },
+    ContentBlockDelta {
+        index: usize,
+        delta: BlockDelta,
+    },
+    MessageDelta {
+        delta: MessageDelta,
+        #[serde(default)]
+        usage: Option<AnthropicUsage>,
+    },
+    Error {
+        error: Value,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// Converts a stream of Messages API events into OpenAI-style chunks.
+pub(crate) fn translate_stream(
+    events: impl Stream<Item = Result<StreamEvent>> + Send + 'static,
+) -> BoxStream<'static, Result<StreamChunk>> {
+    let stream = async_stream::stream! {
+        let mut events = Box::pin(events);
+        let mut id = String::new();
+        let mut model = String::new();
+        let mut input_tokens = None;
+        let created = unix_now();
+        // Maps content block indices to tool call indices.
+        let mut tool_indices: HashMap<usize, u32> = HashMap::new();
+
+        while let Some(event) = events.next().await {
+            let (delta, finish_reason, usage) = match event {
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+                Ok(StreamEvent::MessageStart { message }) => {
+                    id = message.id;
+                    model = message.model;
+                    input_tokens = message.usage.and_then(|usage| usage.input_tokens);
+                    let delta = StreamDelta {
+                        role: Some("assistant".to_string()),
+                        ..StreamDelta::default()
+                    };
+                    (delta, None, None)
+                }
+                Ok(StreamEvent::ContentBlockStart {
+                    index,
+                    content_block: ContentBlock::ToolUse { id: call_id, name, .. },
+                }) => {
+                    let tool_index = tool_indices.len() as u32;
+                    tool_indices.insert(index, tool_index);
+                    let call = ToolCall {
+                        index: Some(tool_index),
+                        id: Some(call_id),
+                        tool_type: Some("function".to_string()),
+                        function: Some(ToolCallFunction {
+                            name: Some(name),
+                            arguments: Some(String::new()),
+                        }),
+                    };
+                    let delta = StreamDelta {
+                        tool_calls: Some(vec![call]),
+                        ..StreamDelta::default()
+                    };
+                    (delta, None, None)
+                }
+                Ok(StreamEvent::ContentBlockDelta { index, delta }) => {
+                    let delta = match delta {
+                        BlockDelta::TextDelta { text } => StreamDelta {
+                            content: Some(text),
+                            ..StreamDelta::default()
+                        },
+                        BlockDelta::ThinkingDelta { thinking } => StreamDelta {
+                            reasoning_content: Some(thinking),
+                            ..StreamDelta::default()
+                        },
+                        BlockDelta::InputJsonDelta { partial_json } => StreamDelta {
+                            tool_calls: Some(vec![ToolCall {
+                                index: tool_indices.get(&index).copied(),
+                                id: None,
+                                tool_type: None,
+                                function: Some(ToolCallFunction {
+                                    name: None,
+                                    arguments: Some(partial_json),
+                                }),
+                            }]),
+                            ..StreamDelta::default()
+                        },
+                        BlockDelta::Other => continue,
+                    };
+                    (delta, None, None)
+                }
+                Ok(StreamEvent::MessageDelta { delta, usage }) => {
+                    let usage = usage.map(|mut usage| {
+                        usage.input_tokens = usage.input_tokens.or(input_tokens);
+                        usage.into_usage()
+                    });
+                    (StreamDelta::default(), delta.stop_reason, usage)
+                }
+                Ok(StreamEvent::Error { error }) => {
+                    let message = error
+                        .get("message")
+                        .and_then(Value::as_str)
+                        .map_or_else(|| error.to_string(), str::to_string);
+                    yield Err(LlmHubError::StreamError(message));
+                    return;
+                }
+                Ok(StreamEvent::ContentBlockStart { .. }) | Ok(StreamEvent::Other) => continue,
+            };
+            yield Ok(StreamChunk {
+                id: id.clone(),
+                object: "chat.completion.chunk".to_string(),
+                created,
+                model: model.clone(),
+                choices: vec![StreamChoice {
+                    index: 0,
+                    delta,
+                    finish_reason,
+                    logprobs: None,
+                }],
+                usage,
+                system_fingerprint: None,
+            });
+        }
+    };
+    stream.boxed()
+}
+
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::session::Session;
+    use crate::models::models::{CHATGPT, CLAUDE, Model};
+
+    fn session() -> Session {
+        let mut session = Session::new();
+        session.add_message(Message::system("Be brief."));
+        session.add_message(Message::user("Hi"));
+        session.replace_system("Be terse.");
+        session
+    }
+
+    #[test]
+    fn system_is_lifted_out_of_anthropic_messages() {
+        let request = ApiRequest::new(Model::Claude(CLAUDE::Sonnet3_7), Some(&session()));
+        let body = request_body(&request);
+
+        assert_eq!(body["system"], "Be terse.");
+        assert_eq!(
+            body["messages"],
+            json!([{ "role": "user", "content": "Hi" }])
+        );
+    }
+
+    #[test]
+    fn system_stays_in_openai_messages() {
+        let request = ApiRequest::new(Model::ChatGPT(CHATGPT::V4o), Some(&session()));
+        let body = serde_json::to_value(&request).unwrap();
+
+        assert!(body.get("system").is_none());
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["messages"][0]["content"], "Be terse.");
+        assert_eq!(body["messages"][1]["role"], "user");
+    }
+
+    #[test]
+    fn parallel_tool_results_share_one_user_turn() {
+        let call = |id: &str| ToolCall {
+            index: None,
+            id: Some(id.to_string()),
+            tool_type: Some("function".to_string()),
+            function: Some(ToolCallFunction {
+                name: Some("get_weather".to_string()),
+                arguments: Some(r#"{"city":"Paris"}"#.to_string()),
+            }),
+        };
+        let mut assistant = Message::assistant("");
+        assistant.tool_calls = Some(vec![call("call_1"), call("call_2")]);
+        let request = ApiRequest::new(Model::Claude(CLAUDE::Sonnet3_7), None)
+            .add_message(Message::user("Weather?"))
+            .add_message(assistant)
+            .add_message(Message::tool("sunny", "call_1"))
+            .add_message(Message::tool("rainy", "call_2"));
+
+        let messages = request_body(&request)["messages"].clone();
+        assert_eq!(messages.as_array().unwrap().len(), 3);
+        assert_eq!(messages[1]["content"][1]["type"], "tool_use");
+        assert_eq!(
+            messages[1]["content"][1]["input"],
+            json!({ "city": "Paris" })
+        );
+        assert_eq!(
+            messages[2],
+            json!({
+                "role": "user",
+                "content": [
+                    { "type": "tool_result", "tool_use_id": "call_1", "content": "sunny" },
+                    { "type": "tool_result", "tool_use_id": "call_2", "content": "rainy" }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn stop_is_sent_as_stop_sequences_and_response_format_is_ignored() {
+        let mut request = ApiRequest::new(Model::Claude(CLAUDE::Sonnet3_7), None)
+            .add_message(Message::user("Hi"));
+        request.options.stop = Some(vec!["END".to_string()]);
+        request.options.response_format = Some(crate::api::request::ResponseFormat::json_object());
+        let body = request_body(&request);
+
+        assert_eq!(body["stop_sequences"], json!(["END"]));
+        assert!(body.get("stop").is_none());
+        assert!(body.get("response_format").is_none());
+    }
+
+    #[tokio::test]
+    async fn translates_stream_events_into_chunks() {
+        let events: Vec<StreamEvent> = [
+            json!({
+                "type": "message_start",
+                "message": { "id": "msg_1", "model": "claude-3-7-sonnet", "usage": { "input_tokens": 10 } }
+            }),
+            json!({ "type": "content_block_start", "index": 0, "content_block": { "type": "text", "text": "" } }),
+            json!({ "type": "ping" }),
+            json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "text_delta", "text": "Hel" } }),
+            json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "text_delta", "text": "lo" } }),
+            json!({ "type": "message_delta", "delta": { "stop_reason": "end_turn" }, "usage": { "output_tokens": 2 } }),
+            json!({ "type": "message_stop" }),
+        ]
+        .into_iter()
+        .map(|event| serde_json::from_value(event).unwrap())
+        .collect();
+
+        let chunks: Vec<StreamChunk> =
+            translate_stream(futures::stream::iter(events.into_iter().map(Ok)))
+                .map(|chunk| chunk.unwrap())
+                .collect()
+                .await;
+
+        assert_eq!(chunks.len(), 4);
+        assert!(
+            chunks
+                .iter()
+                .all(|chunk| chunk.id == "msg_1" && chunk.model == "claude-3-7-sonnet")
+        );
+        assert_eq!(
+            chunks[0].choices[0].delta.role.as_deref(),
+            Some("assistant")
+        );
+        let text: String = chunks
+            .iter()
+            .filter_map(|chunk| chunk.choices[0].delta.content.clone())
+            .collect();
+        assert_eq!(text, "Hello");
+        let last = &chunks[3];
+        assert_eq!(last.choices[0].finish_reason.as_deref(), Some("end_turn"));
+        let usage = last.usage.as_ref().unwrap();
+        assert_eq!(usage.prompt_tokens, Some(10));
+        assert_eq!(usage.completion_tokens, Some(2));
+        assert_eq!(usage.total_tokens, Some(12));
+    }
+}
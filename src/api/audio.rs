@@ -0,0 +1,186 @@
+use crate::api::multipart::{AudioFile, MultipartForm};
+use crate::api::providers::ApiProvider;
+use crate::utils::error::Result;
+use serde::{Deserialize, Serialize};
+
+/// A speech-to-text request, sent as `multipart/form-data`.
+///
+/// Audio models aren't part of [`Model`](crate::models::models::Model), so
+/// the provider and model id are given explicitly, e.g.
+/// `(ApiProvider::OpenAI, "whisper-1")`.
+#[derive(Debug, Clone)]
+pub struct TranscriptionRequest {
+    pub provider: ApiProvider,
+    pub model: String,
+    pub file: AudioFile,
+    /// ISO-639-1 language of the audio, e.g. `"en"`.
+    pub language: Option<String>,
+    /// Text to guide the model's style or continue a previous segment.
+    pub prompt: Option<String>,
+    pub temperature: Option<f32>,
+    /// `json` (the default), `text`, `srt`, `verbose_json` or `vtt`.
+    pub response_format: Option<String>,
+}
+
+impl TranscriptionRequest {
+    /// Creates a request for audio already in memory.
+    pub fn new(
+        provider: ApiProvider,
+        model: impl Into<String>,
+        data: impl Into<bytes::Bytes>,
+        filename: impl Into<String>,
+    ) -> Self {
+        Self::with_file(provider, model, AudioFile::from_bytes(data, filename))
+    }
+
+    /// Creates a request streaming the audio from a file on disk; a missing
+    /// or unreadable file surfaces as an `IoError` when the request is sent.
+    pub fn from_path(
+        provider: ApiProvider,
+        model: impl Into<String>,
+        path: impl Into<std::path::PathBuf>,
+    ) -> Self {
+        Self::with_file(provider, model, AudioFile::from_path(path))
+    }
+
+    /// Creates a request streaming `len` bytes of audio from `reader`.
+    pub fn from_reader(
+        provider: ApiProvider,
+        model: impl Into<String>,
+        reader: impl tokio::io::AsyncRead + Send + Unpin + 'static,
+        len: u64,
+        filename: impl Into<String>,
+    ) -> Self {
+        Self::with_file(
+            provider,
+            model,
+            AudioFile::from_reader(reader, len, filename),
+        )
+    }
+
+    fn with_file(provider: ApiProvider, model: impl Into<String>, file: AudioFile) -> Self {
+        Self {
+            provider,
+            model: model.into(),
+            file,
+            language: None,
+            prompt: None,
+            temperature: None,
+            response_format: None,
+        }
+    }
+
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    pub fn with_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = Some(prompt.into());
+        self
+    }
+
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn with_response_format(mut self, format: impl Into<String>) -> Self {
+        self.response_format = Some(format.into());
+        self
+    }
+
+    /// Builds the multipart form for this request.
+    pub(crate) fn to_form(&self) -> MultipartForm {
+        let mut form = MultipartForm::new()
+            .text("model", self.model.clone())
+            .file("file", self.file.clone());
+        if let Some(language) = &self.language {
+            form = form.text("language", language.clone());
+        }
+        if let Some(prompt) = &self.prompt {
+            form = form.text("prompt", prompt.clone());
+        }
+        if let Some(temperature) = self.temperature {
+            form = form.text("temperature", temperature.to_string());
+        }
+        if let Some(format) = &self.response_format {
+            form = form.text("response_format", format.clone());
+        }
+        form
+    }
+
+    /// Returns whether the requested format is returned as JSON.
+    pub(crate) fn expects_json(&self) -> bool {
+        self.response_format
+            .as_deref()
+            .is_none_or(|format| format.ends_with("json"))
+    }
+}
+
+/// The transcribed text. Plain-text formats (`text`, `srt`, `vtt`) are
+/// returned verbatim in `text`.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TranscriptionResponse {
+    pub text: String,
+    pub language: Option<String>,
+    pub duration: Option<f64>,
+}
+
+impl TranscriptionResponse {
+    pub(crate) fn parse(body: &[u8], json: bool) -> Result<Self> {
+        if json {
+            return Ok(serde_json::from_slice(body)?);
+        }
+        Ok(Self {
+            text: String::from_utf8_lossy(body).into_owned(),
+            language: None,
+            duration: None,
+        })
+    }
+}
+
+/// A text-to-speech request. Like [`TranscriptionRequest`], it names the
+/// provider and model id explicitly, e.g. `(ApiProvider::OpenAI, "tts-1")`.
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Serialize, Clone)]
+pub struct SpeechRequest {
+    #[serde(skip)]
+    pub provider: ApiProvider,
+    pub model: String,
+    pub input: String,
+    pub voice: String,
+    /// Audio format such as `mp3` (the usual default), `opus`, `aac`,
+    /// `flac` or `wav`.
+    pub response_format: Option<String>,
+    /// Playback speed, typically between 0.25 and 4.0.
+    pub speed: Option<f32>,
+}
+
+impl SpeechRequest {
+    pub fn new(
+        provider: ApiProvider,
+        model: impl Into<String>,
+        input: impl Into<String>,
+        voice: impl Into<String>,
+    ) -> Self {
+        Self {
+            provider,
+            model: model.into(),
+            input: input.into(),
+            voice: voice.into(),
+            response_format: None,
+            speed: None,
+        }
+    }
+
+    pub fn with_response_format(mut self, format: impl Into<String>) -> Self {
+        self.response_format = Some(format.into());
+        self
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = Some(speed);
+        self
+    }
+}
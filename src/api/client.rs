@@ -1,94 +1,1321 @@
-use crate::api::providers::ApiType;
-use crate::api::request::ApiRequest;
-use crate::api::response::{ApiResponse, StreamChunk};
-use crate::utils::error::{LlmHubError, Result};
+use crate::api::anthropic::{self, MessagesResponse, StreamEvent};
+use crate::api::audio::{SpeechRequest, TranscriptionRequest, TranscriptionResponse};
+use crate::api::config::ProviderConfig;
+use crate::api::conversation::Conversation;
+use crate::api::embedding::{EmbeddingData, EmbeddingRequest, EmbeddingResponse, OversizePolicy};
+use crate::api::image::{ImageRequest, ImageResponse};
+use crate::api::interceptor::{Interceptor, MetricsReporter, RequestMetrics};
+use crate::api::message::{Content, Message};
+use crate::api::providers::{ApiProvider, ApiType, AuthScheme};
+use crate::api::request::{ApiRequest, RequestOptions, ResponseFormat, ResponseType};
+use crate::api::response::{ApiResponse, ModelInfo, ModelList, StreamChunk};
+use crate::api::session::Session;
+use crate::api::sse::{DEFAULT_MAX_SSE_LINE_BYTES, SseDecoder};
+use crate::api::stream::StreamAccumulator;
+use crate::models::models::Model;
+use crate::utils::error::{ApiErrorDetails, LlmHubError, Result};
+use crate::utils::rate_limit::RateLimiter;
+use crate::utils::tokens::{AsyncTokenizer, HeuristicTokenizer, Tokenizer, chunk_text};
+use bytes::Bytes;
+use futures::stream::BoxStream;
 use futures::{Stream, StreamExt};
-use reqwest::Client as ReqwestClient;
-use reqwest_eventsource::{Event, EventSource};
+use reqwest::header::{
+    ACCEPT, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, HeaderMap, RETRY_AFTER,
+};
+use reqwest::{Client as ReqwestClient, RequestBuilder, Response, StatusCode};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, HashSet};
 use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio_util::sync::CancellationToken;
+
+/// Maximum number of batches [`Client::embed_stream`] keeps in flight.
+pub const EMBED_STREAM_CONCURRENCY: usize = 4;
+
+/// Marker appended to message content cut by [`OverlongPolicy::Truncate`].
+pub const TRUNCATION_MARKER: &str = "… [truncated]";
+
+/// What [`Client::with_max_message_chars`] does with an overlong message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlongPolicy {
+    /// Cut the content to the cap and append [`TRUNCATION_MARKER`].
+    Truncate,
+    /// Fail the request with a `ConfigError` without sending it.
+    Reject,
+}
+
+/// Default total timeout of a non-streaming request.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default longest wait for the next bytes of a response, which is what
+/// bounds a stream that stalls.
+pub const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Configures and creates a [`Client`].
+#[derive(Clone)]
+pub struct ClientBuilder {
+    api_key: String,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    proxy: Option<String>,
+    proxy_auth: Option<(String, String)>,
+    max_concurrency: Option<usize>,
+}
+
+impl std::fmt::Debug for ClientBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientBuilder")
+            .field("api_key", &"<redacted>")
+            .field("timeout", &self.timeout)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("read_timeout", &self.read_timeout)
+            .field("proxy", &self.proxy)
+            .field(
+                "proxy_auth",
+                &self
+                    .proxy_auth
+                    .as_ref()
+                    .map(|(user, _)| (user, "<redacted>")),
+            )
+            .field("max_concurrency", &self.max_concurrency)
+            .finish()
+    }
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            timeout: Some(DEFAULT_TIMEOUT),
+            connect_timeout: None,
+            read_timeout: Some(DEFAULT_READ_TIMEOUT),
+            proxy: None,
+            proxy_auth: None,
+            max_concurrency: None,
+        }
+    }
+}
+
+impl ClientBuilder {
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = api_key.into();
+        self
+    }
+
+    /// Sets the total time a non-streaming request may take, from connecting
+    /// to reading the whole body. Streams are exempt, since a long generation
+    /// can legitimately outlast any fixed budget; they are bounded by
+    /// [`ClientBuilder::read_timeout`] instead. Defaults to [`DEFAULT_TIMEOUT`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Lets non-streaming requests run without a time limit.
+    pub fn no_timeout(mut self) -> Self {
+        self.timeout = None;
+        self
+    }
+
+    /// Sets the time allowed to establish a connection, for every request.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how long any request, streams included, may wait for the next
+    /// bytes of its response before failing with a `TimeoutError`, so a
+    /// stream that stops sending ends instead of hanging. Defaults to
+    /// [`DEFAULT_READ_TIMEOUT`].
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Lets responses, streams included, pause indefinitely between reads.
+    pub fn no_read_timeout(mut self) -> Self {
+        self.read_timeout = None;
+        self
+    }
+
+    /// Routes all provider traffic through an HTTP(S) proxy.
+    ///
+    /// Without an explicit proxy, the `HTTP_PROXY`/`HTTPS_PROXY`/`NO_PROXY`
+    /// environment variables are honored.
+    pub fn proxy(mut self, url: &str) -> Self {
+        self.proxy = Some(url.to_string());
+        self
+    }
+
+    /// Sets basic-auth credentials for the proxy given to [`ClientBuilder::proxy`].
+    pub fn proxy_auth(mut self, username: &str, password: &str) -> Self {
+        self.proxy_auth = Some((username.to_string(), password.to_string()));
+        self
+    }
+
+    /// Caps the number of requests the client has in flight; see
+    /// [`Client::with_max_concurrency`].
+    pub fn max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = Some(max_concurrency);
+        self
+    }
+
+    /// Creates the client, failing with a `ConfigError` for a malformed
+    /// proxy URL.
+    pub fn build(self) -> Result<Client> {
+        let mut http_client = http_client_builder();
+        if let Some(timeout) = self.connect_timeout {
+            http_client = http_client.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.read_timeout {
+            http_client = http_client.read_timeout(timeout);
+        }
+        if let Some(url) = &self.proxy {
+            let mut proxy = reqwest::Proxy::all(url).map_err(|e| {
+                LlmHubError::ConfigError(format!("Invalid proxy URL '{}': {}", url, e))
+            })?;
+            if let Some((username, password)) = &self.proxy_auth {
+                proxy = proxy.basic_auth(username, password);
+            }
+            http_client = http_client.proxy(proxy);
+        }
+        let client = Client::from_parts(http_client.build()?, self.api_key, self.timeout);
+        Ok(match self.max_concurrency {
+            Some(max_concurrency) => client.with_max_concurrency(max_concurrency),
+            None => client,
+        })
+    }
+}
 
 /// A stateless, low-level client for interacting with LLM provider APIs.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct Client {
     http_client: ReqwestClient,
     api_key: String,
+    provider_headers: HashMap<ApiProvider, HashMap<String, String>>,
+    base_urls: HashMap<ApiProvider, String>,
+    tokenizers: HashMap<String, Arc<dyn Tokenizer>>,
+    async_tokenizers: HashMap<String, Arc<dyn AsyncTokenizer>>,
+    rate_limiter: Arc<RateLimiter>,
+    interceptors: Vec<Arc<dyn Interceptor>>,
+    concurrency: Option<Arc<Semaphore>>,
+    strict_capabilities: bool,
+    guided_decoding: HashSet<ApiProvider>,
+    model_fallbacks: HashMap<ApiProvider, Model>,
+    max_message_chars: Option<(usize, OverlongPolicy)>,
+    max_sse_line_bytes: usize,
+    timeout: Option<Duration>,
+}
+
+impl std::fmt::Debug for Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Client")
+            .field("http_client", &self.http_client)
+            .field("api_key", &"<redacted>")
+            .field("provider_headers", &self.provider_headers.keys())
+            .field("base_urls", &self.base_urls)
+            .field("tokenizers", &self.tokenizers.keys())
+            .field("async_tokenizers", &self.async_tokenizers.keys())
+            .field("rate_limiter", &self.rate_limiter)
+            .field("interceptors", &self.interceptors.len())
+            .field("concurrency", &self.concurrency)
+            .field("strict_capabilities", &self.strict_capabilities)
+            .field("guided_decoding", &self.guided_decoding)
+            .field("model_fallbacks", &self.model_fallbacks)
+            .field("max_message_chars", &self.max_message_chars)
+            .field("max_sse_line_bytes", &self.max_sse_line_bytes)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
 }
 
 impl Client {
     /// Creates a new `Client`.
+    ///
+    /// Non-streaming requests time out after [`DEFAULT_TIMEOUT`]; use
+    /// [`Client::builder`] to change that.
     pub fn new(api_key: String) -> Self {
+        Self::builder()
+            .api_key(api_key)
+            .build()
+            .expect("Failed to build HTTP client")
+    }
+
+    /// Returns a [`ClientBuilder`] for configuring timeouts.
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    fn from_parts(http_client: ReqwestClient, api_key: String, timeout: Option<Duration>) -> Self {
+        Self {
+            http_client,
+            api_key,
+            timeout,
+            provider_headers: HashMap::new(),
+            base_urls: HashMap::new(),
+            tokenizers: HashMap::new(),
+            async_tokenizers: HashMap::new(),
+            rate_limiter: Arc::new(RateLimiter::new()),
+            interceptors: Vec::new(),
+            concurrency: None,
+            strict_capabilities: false,
+            guided_decoding: HashSet::new(),
+            model_fallbacks: HashMap::new(),
+            max_message_chars: None,
+            max_sse_line_bytes: DEFAULT_MAX_SSE_LINE_BYTES,
+        }
+    }
+
+    /// Controls what happens when a request uses an option its provider does
+    /// not support, such as a JSON `response_format` or guided decoding:
+    /// when `strict`, the request fails with a `ProviderError`; otherwise
+    /// (the default) the option is dropped and a debug message is logged.
+    pub fn with_strict_capabilities(mut self, strict: bool) -> Self {
+        self.strict_capabilities = strict;
+        self
+    }
+
+    /// Marks `provider`'s endpoint as accepting vLLM-style guided decoding
+    /// (`guided_json`, `guided_regex`, `guided_grammar`), typically together
+    /// with [`Client::with_base_url`] pointing it at a self-hosted server.
+    /// Without this, guided constraints are handled like any other option the
+    /// provider doesn't support.
+    pub fn with_guided_decoding(mut self, provider: ApiProvider) -> Self {
+        self.guided_decoding.insert(provider);
+        self
+    }
+
+    /// Sets a fallback for requests to `model`'s provider: when [`Client::chat`]
+    /// gets a 404 (typically a renamed or retired model id), it retries once
+    /// with `model` and logs the substitution. Off unless configured.
+    pub fn with_model_fallback(mut self, model: Model) -> Self {
+        self.model_fallbacks.insert(model.provider(), model);
+        self
+    }
+
+    /// Caps the content of each outgoing chat message at `max_chars`
+    /// characters, as a guard against unbounded pastes. Overlong messages are
+    /// truncated (and logged) or rejected according to `policy`.
+    pub fn with_max_message_chars(mut self, max_chars: usize, policy: OverlongPolicy) -> Self {
+        self.max_message_chars = Some((max_chars, policy));
+        self
+    }
+
+    /// Sets the longest SSE line or event payload a stream may send before it
+    /// is aborted with a `StreamError`. Defaults to
+    /// [`DEFAULT_MAX_SSE_LINE_BYTES`].
+    pub fn with_max_sse_line_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_sse_line_bytes = max_bytes;
+        self
+    }
+
+    /// Caps the number of requests this client (and its clones) has in flight.
+    ///
+    /// Calls beyond the cap wait for a slot rather than failing. A streaming
+    /// request holds its slot until the stream is dropped or finishes. A cap
+    /// of `0` removes the limit, since no request could ever proceed.
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.concurrency = (max_concurrency > 0).then(|| Arc::new(Semaphore::new(max_concurrency)));
+        self
+    }
+
+    /// Registers an interceptor; interceptors run in registration order.
+    pub fn with_interceptor(mut self, interceptor: Arc<dyn Interceptor>) -> Self {
+        self.interceptors.push(interceptor);
+        self
+    }
+
+    /// Throttles requests to a provider on the client side. Clients don't
+    /// throttle unless configured to.
+    ///
+    /// The returned client gets its own limiter, so clones made before this
+    /// call keep their previous limits.
+    pub fn with_rate_limit(mut self, provider: ApiProvider, requests_per_minute: u32) -> Self {
+        let rate_limiter = self.rate_limiter.with_same_limits();
+        rate_limiter.set_requests_per_minute(provider, requests_per_minute);
+        self.rate_limiter = Arc::new(rate_limiter);
+        self
+    }
+
+    /// Throttles every provider to its conservative
+    /// [`default_requests_per_minute`](crate::utils::rate_limit::default_requests_per_minute),
+    /// dropping any per-provider rates set so far.
+    pub fn with_default_rate_limits(mut self) -> Self {
+        self.rate_limiter = Arc::new(RateLimiter::with_provider_defaults());
+        self
+    }
+
+    /// Disables client-side rate limiting, including rates set so far, e.g.
+    /// for tests or batch jobs that pace themselves. Providers may then
+    /// answer bursts with HTTP 429.
+    pub fn without_rate_limit(mut self) -> Self {
+        self.rate_limiter = Arc::new(RateLimiter::unlimited());
+        self
+    }
+
+    /// Sends requests for `provider` to `base_url` instead of its default,
+    /// e.g. a self-hosted gateway or proxy.
+    pub fn with_base_url(mut self, provider: ApiProvider, base_url: impl Into<String>) -> Self {
+        self.base_urls.insert(provider, base_url.into());
+        self
+    }
+
+    /// Applies a provider configuration's base URL, extra headers and rate limit.
+    ///
+    /// A base URL equal to the provider's default is not treated as an
+    /// override.
+    pub fn with_provider_config(self, config: &ProviderConfig) -> Self {
+        let default_base_url = config.api_provider.base_url().trim_end_matches('/');
+        let client = match &config.api_base_url {
+            Some(base_url) if base_url.trim_end_matches('/') != default_base_url => {
+                self.with_base_url(config.api_provider, base_url.clone())
+            }
+            _ => self,
+        };
+        let client = client.with_provider_headers(config);
+        match config.requests_per_minute {
+            Some(rpm) => client.with_rate_limit(config.api_provider, rpm),
+            None => client,
+        }
+    }
+
+    /// Registers the tokenizer used to count tokens for `model`.
+    pub fn with_tokenizer(mut self, model: &Model, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizers.insert(model.to_string(), tokenizer);
+        self
+    }
+
+    /// Returns the tokenizer registered for `model`, or the heuristic default.
+    pub fn tokenizer_for(&self, model: &Model) -> Arc<dyn Tokenizer> {
+        self.tokenizers
+            .get(&model.to_string())
+            .cloned()
+            .unwrap_or_else(|| Arc::new(HeuristicTokenizer))
+    }
+
+    /// Registers an [`AsyncTokenizer`] for `model`, e.g. one backed by a
+    /// provider's token-counting endpoint. It takes precedence over a
+    /// [`Tokenizer`] registered for the same model in [`Client::count_tokens`].
+    pub fn with_async_tokenizer(
+        mut self,
+        model: &Model,
+        tokenizer: Arc<dyn AsyncTokenizer>,
+    ) -> Self {
+        self.async_tokenizers.insert(model.to_string(), tokenizer);
+        self
+    }
+
+    /// Counts the tokens `messages` occupy for `model`, using its async
+    /// tokenizer if one is registered and [`Client::tokenizer_for`] otherwise.
+    pub async fn count_tokens(&self, model: &Model, messages: &[Message]) -> usize {
+        match self.async_tokenizers.get(&model.to_string()) {
+            Some(tokenizer) => tokenizer.count_messages(messages).await,
+            None => self.tokenizer_for(model).count_messages(messages),
+        }
+    }
+
+    /// Returns a client using a different API key, e.g. one per tenant.
+    ///
+    /// The underlying `reqwest` connection pool, headers, tokenizers and
+    /// interceptors are shared with `self`; rate-limit buckets are not, since
+    /// each key has its own provider quota.
+    pub fn with_key(&self, api_key: String) -> Self {
         Self {
-            http_client: ReqwestClient::new(),
             api_key,
+            rate_limiter: Arc::new(self.rate_limiter.with_same_limits()),
+            ..self.clone()
         }
     }
 
+    /// Registers the `extra_headers` of a provider configuration, which are
+    /// then sent with every request routed to that provider.
+    pub fn with_provider_headers(mut self, config: &ProviderConfig) -> Self {
+        if let Some(headers) = &config.extra_headers {
+            self.provider_headers
+                .entry(config.api_provider)
+                .or_default()
+                .extend(headers.clone());
+        }
+        self
+    }
+
+    /// Creates a `Client` from a provider configuration, sending requests to
+    /// its `api_base_url` when set (e.g. a self-hosted gateway) and to the
+    /// provider's default endpoint otherwise.
+    pub fn from_config(config: ProviderConfig) -> Result<Self> {
+        let api_key = config.api_key.clone().ok_or_else(|| {
+            LlmHubError::ConfigError(format!("No API key configured for {}", config.api_provider))
+        })?;
+        Ok(Self::new(api_key).with_provider_config(&config))
+    }
+
+    /// Creates a new `Client` using the API key from the provider's
+    /// `<PREFIX>_API_KEY` environment variable, and the base URL from
+    /// `<PREFIX>_API_BASE` when set.
+    pub fn from_env(provider: ApiProvider) -> Result<Self> {
+        let config = ProviderConfig::from_env(provider).ok_or_else(|| {
+            LlmHubError::ConfigError(format!(
+                "{}_API_KEY is not set for provider {}",
+                provider.env_prefix(),
+                provider
+            ))
+        })?;
+        Self::from_config(config)
+    }
+
     /// Sends a standard, non-streaming chat request.
     pub async fn chat(&self, request: &ApiRequest) -> Result<ApiResponse> {
+        match self.chat_once(request).await {
+            Err(LlmHubError::ApiErrorResponse(details)) if details.status == 404 => {
+                let fallback = self
+                    .model_fallbacks
+                    .get(&request.model.provider())
+                    .filter(|fallback| *fallback != &request.model);
+                let Some(fallback) = fallback else {
+                    return Err(LlmHubError::ApiErrorResponse(details));
+                };
+                log::warn!(
+                    "Model {} unavailable ({}), retrying with fallback {}",
+                    request.model,
+                    details,
+                    fallback
+                );
+                let mut request = request.clone();
+                request.model = fallback.clone();
+                self.chat_once(&request).await
+            }
+            result => result,
+        }
+    }
+
+    async fn chat_once(&self, request: &ApiRequest) -> Result<ApiResponse> {
+        let mut request = request.clone();
+        self.transform_request(&mut request);
+        // The non-streaming path can't parse SSE, so never let a stray
+        // `stream: true` option through.
+        request.options.stream = Some(false);
+
         let provider = request.model.provider();
-        let endpoint_config = provider.get_endpoint_config();
-        let url = endpoint_config.get_url(ApiType::Chat)?;
+        self.apply_capabilities(provider, &mut request)?;
+        self.enforce_message_cap(&mut request)?;
+        let builder = self.post(provider, ApiType::Chat, false)?;
+        let builder = with_request_headers(provider, builder, &request.headers);
+        let (builder, request_bytes) = if provider == ApiProvider::Anthropic {
+            json_body(builder, &anthropic::request_body(&request))?
+        } else {
+            json_body(builder, &request)?
+        };
+        let mut reporter = self.metrics_reporter(provider, ApiType::Chat, false, request_bytes);
+
+        let response = self.send(provider, builder).await?;
+        let body = response.bytes().await?;
+        reporter.metrics.response_bytes = body.len();
+        let mut api_response = if provider == ApiProvider::Anthropic {
+            serde_json::from_slice::<MessagesResponse>(&body)?.into_api_response()
+        } else {
+            serde_json::from_slice::<ApiResponse>(&body)?
+        };
+        for interceptor in &self.interceptors {
+            interceptor.on_response(&mut api_response);
+        }
+        if api_response.choices.is_empty() {
+            return Err(LlmHubError::ApiError(format!(
+                "Response {} from model {} contained no choices (usage: {:?}, system_fingerprint: {:?})",
+                api_response.id,
+                api_response.model,
+                api_response.usage,
+                api_response.system_fingerprint
+            )));
+        }
+        Ok(api_response)
+    }
 
-        let response = self
-            .http_client
-            .post(url)
-            .bearer_auth(&self.api_key)
-            .json(request)
-            .send()
-            .await?;
+    /// Creates embeddings for a batch of inputs, returned in input order.
+    ///
+    /// When the request carries an [`OversizePolicy`] and the model's input
+    /// limit is known, inputs over the limit (as counted by the model's
+    /// tokenizer) are rejected, truncated or split before sending.
+    pub async fn embeddings(&self, request: &EmbeddingRequest) -> Result<EmbeddingResponse> {
+        if !request.model.is_embedding() {
+            return Err(LlmHubError::ProviderError(format!(
+                "Model '{}' is not an embedding model",
+                request.model
+            )));
+        }
+        match (
+            request.oversize_policy,
+            request.model.max_embedding_input_tokens(),
+        ) {
+            (Some(policy), Some(limit)) => {
+                self.embeddings_within_limit(request, policy, limit as usize)
+                    .await
+            }
+            _ => self.send_embeddings(request).await,
+        }
+    }
+
+    async fn embeddings_within_limit(
+        &self,
+        request: &EmbeddingRequest,
+        policy: OversizePolicy,
+        limit: usize,
+    ) -> Result<EmbeddingResponse> {
+        let tokenizer = self.tokenizer_for(&request.model);
+        let mut input = Vec::new();
+        let mut owners = Vec::new();
+        for (index, text) in request.input.iter().enumerate() {
+            if tokenizer.count(text) <= limit {
+                input.push(text.clone());
+                owners.push(index);
+                continue;
+            }
+            match policy {
+                OversizePolicy::Error => {
+                    return Err(LlmHubError::ConfigError(format!(
+                        "Embedding input {} exceeds the {}-token limit of {}",
+                        index, limit, request.model
+                    )));
+                }
+                OversizePolicy::Truncate => {
+                    let head = chunk_text(text, limit, 0, tokenizer.as_ref())
+                        .into_iter()
+                        .next()
+                        .unwrap_or_default();
+                    input.push(head);
+                    owners.push(index);
+                }
+                OversizePolicy::Split => {
+                    for chunk in chunk_text(text, limit, 0, tokenizer.as_ref()) {
+                        input.push(chunk);
+                        owners.push(index);
+                    }
+                }
+            }
+        }
+
+        let split_request = EmbeddingRequest {
+            input,
+            oversize_policy: None,
+            ..request.clone()
+        };
+        let response = self.send_embeddings(&split_request).await?;
+        let mut sums: Vec<(Vec<f32>, usize)> = vec![(Vec::new(), 0); request.input.len()];
+        for data in response.data {
+            let Some(&owner) = owners.get(data.index) else {
+                continue;
+            };
+            let (sum, count) = &mut sums[owner];
+            if sum.is_empty() {
+                *sum = data.embedding;
+            } else {
+                for (total, value) in sum.iter_mut().zip(data.embedding) {
+                    *total += value;
+                }
+            }
+            *count += 1;
+        }
+        let data = sums
+            .into_iter()
+            .enumerate()
+            .filter(|(_, (_, count))| *count > 0)
+            .map(|(index, (mut embedding, count))| {
+                if count > 1 {
+                    // The mean of unit vectors is shorter than unit length, so
+                    // rescale it to match what the model returns for one chunk.
+                    let norm = embedding.iter().map(|v| v * v).sum::<f32>().sqrt();
+                    if norm > 0.0 {
+                        embedding.iter_mut().for_each(|v| *v /= norm);
+                    }
+                }
+                EmbeddingData { index, embedding }
+            })
+            .collect();
+        Ok(EmbeddingResponse {
+            data,
+            model: response.model,
+            usage: response.usage,
+        })
+    }
+
+    async fn send_embeddings(&self, request: &EmbeddingRequest) -> Result<EmbeddingResponse> {
+        let provider = request.model.provider();
+        let (builder, request_bytes) =
+            json_body(self.post(provider, ApiType::Embedding, false)?, request)?;
+        let mut reporter =
+            self.metrics_reporter(provider, ApiType::Embedding, false, request_bytes);
+        let response = self.send(provider, builder).await?;
+        let body = response.bytes().await?;
+        reporter.metrics.response_bytes = body.len();
+        let mut embeddings: EmbeddingResponse = serde_json::from_slice(&body)?;
+        // Providers aren't required to return vectors in input order.
+        embeddings.data.sort_by_key(|data| data.index);
+        Ok(embeddings)
+    }
+
+    /// Embeds a stream of inputs in batches of `batch_size`, yielding
+    /// `(original_index, embedding)` pairs as each batch completes.
+    ///
+    /// Up to [`EMBED_STREAM_CONCURRENCY`] batches are in flight at once, so
+    /// results may arrive out of input order; nothing is buffered beyond the
+    /// in-flight batches, keeping memory bounded for arbitrarily large corpora.
+    pub fn embed_stream(
+        &self,
+        model: Model,
+        inputs: impl Stream<Item = String> + Send + 'static,
+        batch_size: usize,
+    ) -> BoxStream<'static, Result<(usize, Vec<f32>)>> {
+        let client = self.clone();
+        let stream = inputs
+            .enumerate()
+            .chunks(batch_size.max(1))
+            .map(move |batch| {
+                let client = client.clone();
+                let model = model.clone();
+                async move {
+                    let offset = batch.first().map_or(0, |(i, _)| *i);
+                    let request =
+                        EmbeddingRequest::new(model, batch.into_iter().map(|(_, s)| s).collect());
+                    let response = client.embeddings(&request).await?;
+                    Ok::<_, LlmHubError>(
+                        response
+                            .data
+                            .into_iter()
+                            .map(move |d| (offset + d.index, d.embedding))
+                            .collect::<Vec<_>>(),
+                    )
+                }
+            })
+            .buffer_unordered(EMBED_STREAM_CONCURRENCY)
+            .flat_map(|batch| {
+                let items: Vec<_> = match batch {
+                    Ok(items) => items.into_iter().map(Ok).collect(),
+                    Err(e) => vec![Err(e)],
+                };
+                futures::stream::iter(items)
+            });
+        Box::pin(stream)
+    }
+
+    /// Generates images from a text prompt.
+    ///
+    /// Providers without an image generation endpoint fail with a
+    /// `ProviderError` before anything is sent.
+    pub async fn generate_image(&self, request: &ImageRequest) -> Result<ImageResponse> {
+        let provider = request.model.provider();
+        let (builder, request_bytes) = json_body(
+            self.post(provider, ApiType::ImageGeneration, false)?,
+            request,
+        )?;
+        let mut reporter =
+            self.metrics_reporter(provider, ApiType::ImageGeneration, false, request_bytes);
+        let response = self.send(provider, builder).await?;
+        let body = response.bytes().await?;
+        reporter.metrics.response_bytes = body.len();
+        serde_json::from_slice(&body).map_err(LlmHubError::from)
+    }
+
+    /// Transcribes audio to text.
+    ///
+    /// The audio is uploaded as `multipart/form-data`, streaming path-based
+    /// files from disk.
+    pub async fn transcribe(
+        &self,
+        request: &TranscriptionRequest,
+    ) -> Result<TranscriptionResponse> {
+        let provider = request.provider;
+        let builder = self.post(provider, ApiType::AudioTranscription, false)?;
+        let form = request.to_form().into_body().await?;
+        let builder = builder
+            .header(CONTENT_TYPE, form.content_type)
+            .header(CONTENT_LENGTH, form.content_length)
+            .body(form.body);
+        let mut reporter = self.metrics_reporter(
+            provider,
+            ApiType::AudioTranscription,
+            false,
+            form.content_length as usize,
+        );
+        let response = self.send(provider, builder).await?;
+        let body = response.bytes().await?;
+        reporter.metrics.response_bytes = body.len();
+        TranscriptionResponse::parse(&body, request.expects_json())
+    }
+
+    /// Synthesizes speech, returning the encoded audio (e.g. MP3 bytes ready
+    /// to be written to a file).
+    pub async fn speech(&self, request: &SpeechRequest) -> Result<Bytes> {
+        let provider = request.provider;
+        let (builder, request_bytes) =
+            json_body(self.post(provider, ApiType::AudioSpeech, false)?, request)?;
+        let mut reporter =
+            self.metrics_reporter(provider, ApiType::AudioSpeech, false, request_bytes);
+        let response = self.send(provider, builder).await?;
+        let audio = response.bytes().await?;
+        reporter.metrics.response_bytes = audio.len();
+        Ok(audio)
+    }
+
+    /// Downloads a file, such as a generated image, from a provider URL.
+    ///
+    /// Provider URLs are typically pre-signed, so no credentials are sent.
+    pub async fn download(&self, url: &str) -> Result<Bytes> {
+        let response = self.http_client.get(url).send().await?;
+        if !response.status().is_success() {
+            return Err(LlmHubError::ApiError(format!(
+                "Download of {} failed with status {}",
+                url,
+                response.status()
+            )));
+        }
+        Ok(response.bytes().await?)
+    }
+
+    /// Lists the models available from `provider`, via a GET to its models
+    /// endpoint. Providers without one fail with a `ProviderError`.
+    ///
+    /// Useful for discovering the model ids a provider serves at runtime,
+    /// beyond those in [`Model`].
+    pub async fn list_models(&self, provider: ApiProvider) -> Result<Vec<ModelInfo>> {
+        let builder = self.get(provider, ApiType::ListModels)?;
+        let response = self.send(provider, builder).await?;
+        let list: ModelList = response.json().await?;
+        Ok(list.data)
+    }
+
+    /// Starts a stateful multi-turn [`Conversation`] with `model`.
+    pub fn conversation(&self, model: Model, options: RequestOptions) -> Conversation {
+        Conversation::new(self.clone(), model, options)
+    }
+
+    /// Sends a non-streaming chat request built from a model and messages.
+    pub async fn chat_messages(
+        &self,
+        model: Model,
+        messages: Vec<Message>,
+        options: RequestOptions,
+    ) -> Result<ApiResponse> {
+        let request = ApiRequest::new(model, None)
+            .with_options(options)
+            .with_messages(messages);
+        self.chat(&request).await
+    }
+
+    /// Sends a non-streaming chat request for a session, letting `select`
+    /// pick the model from the messages at send time, e.g. a small model
+    /// unless the prompt is large.
+    pub async fn chat_routed(
+        &self,
+        select: impl FnOnce(&[Message]) -> Model,
+        session: &Session,
+        options: RequestOptions,
+    ) -> Result<ApiResponse> {
+        let messages = session.get_messages().clone();
+        let model = select(&messages);
+        self.chat_messages(model, messages, options).await
+    }
+
+    /// Sends a streaming chat request built from a model and messages.
+    pub fn chat_messages_stream(
+        &self,
+        model: Model,
+        messages: Vec<Message>,
+        options: RequestOptions,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamChunk>> + Send>>> {
+        let request = ApiRequest::new(model, None)
+            .with_options(options)
+            .with_messages(messages);
+        self.chat_stream(&request)
+    }
+
+    /// Sends a chat request asking for a JSON reply and deserializes it into
+    /// `T`.
+    ///
+    /// `response_format` defaults to `json_object` unless `options` already
+    /// sets one. Content that doesn't match `T` is a `SerializationError`.
+    pub async fn chat_structured<T: DeserializeOwned>(
+        &self,
+        model: Model,
+        messages: Vec<Message>,
+        mut options: RequestOptions,
+    ) -> Result<T> {
+        options
+            .response_format
+            .get_or_insert_with(ResponseFormat::json_object);
+        self.chat_messages(model, messages, options).await?.json()
+    }
+
+    /// Sends a chat request and parses the reply as JSON, re-requesting up to
+    /// `max_retries` times when the model returns content that fails to parse.
+    ///
+    /// When `corrective` is set, each retry appends the invalid reply and a
+    /// user message with that text (e.g. "Return valid JSON only") to the
+    /// conversation. Returns the first successful parse or the last error.
+    pub async fn chat_with_retry_on_parse<T: DeserializeOwned>(
+        &self,
+        request: &ApiRequest,
+        max_retries: usize,
+        corrective: Option<&str>,
+    ) -> Result<T> {
+        let mut request = request.clone();
+        let mut attempt = 0;
+        loop {
+            let response = self.chat(&request).await?;
+            match response.json::<T>() {
+                Err(LlmHubError::SerializationError(e)) if attempt < max_retries => {
+                    log::warn!("Retrying after invalid JSON reply: {}", e);
+                    attempt += 1;
+                    if let Some(corrective) = corrective {
+                        if let Some(content) = response.content() {
+                            request.messages.push(Message::assistant(content));
+                        }
+                        request.messages.push(Message::user(corrective));
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Opens a connection to `provider` so the first real request doesn't pay
+    /// for DNS and the TLS handshake.
+    ///
+    /// Sends an unauthenticated `HEAD` to the provider's base URL. This is
+    /// best-effort: failures and error statuses are logged and ignored.
+    pub async fn warmup(&self, provider: ApiProvider) {
+        let base_url = self
+            .base_urls
+            .get(&provider)
+            .map_or_else(|| provider.base_url(), String::as_str);
+        match self.http_client.head(base_url).send().await {
+            Ok(response) => log::debug!(
+                "Warmed up connection to {} ({})",
+                provider,
+                response.status()
+            ),
+            Err(e) => log::debug!("Warmup for {} failed: {}", provider, e),
+        }
+    }
+
+    /// Resolves the URL of an endpoint, honoring any base URL override.
+    fn endpoint_url(&self, provider: ApiProvider, api_type: ApiType) -> Result<String> {
+        let mut endpoint_config = provider.get_endpoint_config();
+        if let Some(base_url) = self.base_urls.get(&provider) {
+            endpoint_config.base_url = base_url.trim_end_matches('/').to_string();
+        }
+        endpoint_config.get_url(api_type)
+    }
+
+    /// Sends a request once an in-flight slot and a rate-limit token are
+    /// available. Non-success statuses are converted by [`error_from_response`].
+    async fn send(&self, provider: ApiProvider, builder: RequestBuilder) -> Result<Response> {
+        let _permit = acquire_permit(&self.concurrency).await;
+        self.rate_limiter.acquire(provider).await;
+        let response = builder.send().await?;
 
         if response.status().is_success() {
-            response.json().await.map_err(LlmHubError::from)
+            Ok(response)
         } else {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown API error".to_string());
-            Err(LlmHubError::ApiError(error_text))
+            Err(error_from_response(response).await)
+        }
+    }
+
+    /// Drops, or rejects in strict mode, options the provider doesn't support.
+    /// See [`RequestOptions::normalize_for`] for the per-model adjustments.
+    /// An unsupported JSON schema response format is always rejected.
+    fn apply_capabilities(&self, provider: ApiProvider, request: &mut ApiRequest) -> Result<()> {
+        request.validate()?;
+        if let Some(format) = &request.options.response_format
+            && (self.strict_capabilities
+                || matches!(format.response_type, ResponseType::JsonSchema))
+            && !provider.supports_response_format(&format.response_type)
+        {
+            return Err(LlmHubError::ProviderError(format!(
+                "Provider {} does not support response_format {:?}",
+                provider, format.response_type
+            )));
+        }
+        request.options = request.options.normalize_for(&request.model);
+        if request.options.has_guided_decoding() && !self.guided_decoding.contains(&provider) {
+            if self.strict_capabilities {
+                return Err(LlmHubError::ProviderError(format!(
+                    "Provider {} does not support guided decoding",
+                    provider
+                )));
+            }
+            log::debug!(
+                "Dropping guided decoding constraints unsupported by {}",
+                provider
+            );
+            request.options.guided_json = None;
+            request.options.guided_regex = None;
+            request.options.guided_grammar = None;
+        }
+        Ok(())
+    }
+
+    fn metrics_reporter(
+        &self,
+        provider: ApiProvider,
+        api_type: ApiType,
+        streaming: bool,
+        request_bytes: usize,
+    ) -> MetricsReporter {
+        MetricsReporter {
+            interceptors: self.interceptors.clone(),
+            metrics: RequestMetrics {
+                provider,
+                api_type,
+                streaming,
+                request_bytes,
+                response_bytes: 0,
+            },
         }
     }
 
+    /// Truncates or rejects messages over the configured character cap.
+    fn enforce_message_cap(&self, request: &mut ApiRequest) -> Result<()> {
+        let Some((max_chars, policy)) = self.max_message_chars else {
+            return Ok(());
+        };
+        for (index, message) in request.messages.iter_mut().enumerate() {
+            let texts = message
+                .content
+                .as_mut()
+                .map(Content::texts_mut)
+                .unwrap_or_default();
+            for text in texts {
+                let Some((cut, _)) = text.char_indices().nth(max_chars) else {
+                    continue;
+                };
+                match policy {
+                    OverlongPolicy::Reject => {
+                        return Err(LlmHubError::ConfigError(format!(
+                            "Message {} exceeds the {}-character cap",
+                            index, max_chars
+                        )));
+                    }
+                    OverlongPolicy::Truncate => {
+                        log::warn!(
+                            "Truncating message {} from {} bytes to {} characters",
+                            index,
+                            text.len(),
+                            max_chars
+                        );
+                        text.truncate(cut);
+                        text.push_str(TRUNCATION_MARKER);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn transform_request(&self, request: &mut ApiRequest) {
+        for interceptor in &self.interceptors {
+            interceptor.transform_request(request);
+        }
+    }
+
+    /// Builds a POST request to an endpoint, authenticated per the provider's
+    /// [`AuthScheme`] and carrying the provider's extra headers and the
+    /// `Accept` header for `api_type`.
+    ///
+    /// Streaming requests accept `text/event-stream`; `Content-Type` is set
+    /// by the body (`.json()` or a multipart body).
+    fn post(
+        &self,
+        provider: ApiProvider,
+        api_type: ApiType,
+        streaming: bool,
+    ) -> Result<RequestBuilder> {
+        let url = self.endpoint_url(provider, api_type)?;
+        let builder = self.http_client.post(url);
+        let builder = match (streaming, self.timeout) {
+            (true, _) => builder.header(ACCEPT, "text/event-stream"),
+            (false, Some(timeout)) => builder.header(ACCEPT, api_type.accept()).timeout(timeout),
+            (false, None) => builder.header(ACCEPT, api_type.accept()),
+        };
+        Ok(self.authorize(provider, builder))
+    }
+
+    /// Builds an authenticated GET request to an endpoint.
+    fn get(&self, provider: ApiProvider, api_type: ApiType) -> Result<RequestBuilder> {
+        let url = self.endpoint_url(provider, api_type)?;
+        let mut builder = self.http_client.get(url).header(ACCEPT, api_type.accept());
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        Ok(self.authorize(provider, builder))
+    }
+
+    /// Adds the provider's credentials and extra headers to a request.
+    fn authorize(&self, provider: ApiProvider, builder: RequestBuilder) -> RequestBuilder {
+        let mut builder = match provider.auth_scheme() {
+            AuthScheme::Bearer => builder.bearer_auth(&self.api_key),
+            AuthScheme::Header(name) => builder.header(name, &self.api_key),
+            AuthScheme::QueryParam(name) => builder.query(&[(name, &self.api_key)]),
+        };
+        let headers = self.provider_headers.get(&provider);
+        for (key, value) in provider.default_headers() {
+            if !headers.is_some_and(|h| h.keys().any(|k| k.eq_ignore_ascii_case(key))) {
+                builder = builder.header(*key, *value);
+            }
+        }
+        for (key, value) in headers.into_iter().flatten() {
+            builder = builder.header(key, value);
+        }
+        builder
+    }
+
     /// Sends a streaming chat request.
+    ///
+    /// The stream owns the HTTP response body, so dropping it before the end
+    /// (e.g. when the user cancels) aborts the response immediately rather
+    /// than reading it until the server finishes: an HTTP/1.1 connection is
+    /// closed, while over HTTP/2 only the stream is reset and the shared
+    /// connection stays open for other requests.
     pub fn chat_stream(
         &self,
         request: &ApiRequest,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamChunk>> + Send>>> {
+        let mut request = request.clone();
+        self.transform_request(&mut request);
+        request.options.stream = Some(true);
+
         let provider = request.model.provider();
-        let endpoint_config = provider.get_endpoint_config();
-        let url = endpoint_config.get_url(ApiType::Chat)?;
+        self.apply_capabilities(provider, &mut request)?;
+        self.enforce_message_cap(&mut request)?;
+        let interceptors = self.interceptors.clone();
 
-        let mut es = EventSource::new(
-            self.http_client
-                .post(url)
-                .bearer_auth(&self.api_key)
-                .json(&request),
-        )
-        .expect("Failed to create EventSource");
+        let chunks = if provider == ApiProvider::Anthropic {
+            let body = anthropic::request_body(&request);
+            let events =
+                self.stream_sse::<StreamEvent>(provider, ApiType::Chat, &body, &request.headers)?;
+            anthropic::translate_stream(events)
+        } else {
+            self.stream_sse::<StreamChunk>(provider, ApiType::Chat, &request, &request.headers)?
+        };
+        let stream = chunks.map(move |chunk| {
+            chunk.map(|mut chunk| {
+                for interceptor in &interceptors {
+                    interceptor.on_stream_chunk(&mut chunk);
+                }
+                chunk
+            })
+        });
 
+        Ok(Box::pin(stream))
+    }
+
+    /// Sends a streaming chat request that ends as soon as `cancel` fires,
+    /// e.g. when the user stops generation.
+    ///
+    /// On cancellation the stream ends without an error and the response is
+    /// dropped, aborting it as described for [`Client::chat_stream`].
+    pub fn chat_stream_with_cancel(
+        &self,
+        request: &ApiRequest,
+        cancel: CancellationToken,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamChunk>> + Send>>> {
+        let mut chunks = self.chat_stream(request)?;
         let stream = async_stream::stream! {
-            while let Some(event) = es.next().await {
-                match event {
-                    Ok(Event::Open) => continue,
-                    Ok(Event::Message(message)) => {
+            loop {
+                tokio::select! {
+                    biased;
+                    _ = cancel.cancelled() => break,
+                    chunk = chunks.next() => match chunk {
+                        Some(chunk) => yield chunk,
+                        None => break,
+                    },
+                }
+            }
+        };
+        Ok(Box::pin(stream))
+    }
 
-                        if message.data == "[DONE]" {
-                            break;
-                        }
-                        let chunk: StreamChunk = match serde_json::from_str(&message.data) {
-                            Ok(c) => {
-                                c
-                            },
+    /// Sends a streaming chat request and returns the provider's SSE body as
+    /// raw bytes, for proxies that forward the stream verbatim.
+    ///
+    /// Connection and HTTP status errors are reported as for [`Client::chat`],
+    /// but the body is not parsed: SSE framing, keep-alives and the final
+    /// `[DONE]` event are passed through untouched, and `on_stream_chunk`
+    /// interceptors are not run. Anthropic streams keep their native event
+    /// format.
+    pub async fn chat_stream_raw(
+        &self,
+        request: &ApiRequest,
+    ) -> Result<BoxStream<'static, Result<Bytes>>> {
+        let mut request = request.clone();
+        self.transform_request(&mut request);
+        request.options.stream = Some(true);
+
+        let provider = request.model.provider();
+        self.apply_capabilities(provider, &mut request)?;
+        self.enforce_message_cap(&mut request)?;
+        let builder = self.post(provider, ApiType::Chat, true)?;
+        let builder = with_request_headers(provider, builder, &request.headers);
+        let (builder, request_bytes) = if provider == ApiProvider::Anthropic {
+            json_body(builder, &anthropic::request_body(&request))?
+        } else {
+            json_body(builder, &request)?
+        };
+        let mut reporter = self.metrics_reporter(provider, ApiType::Chat, true, request_bytes);
+
+        let response = self.send(provider, builder).await?;
+        Ok(response
+            .bytes_stream()
+            .map(move |chunk| {
+                // Move the whole reporter in, not just the counter, so it
+                // reports when the stream is dropped.
+                let reporter = &mut reporter;
+                let chunk = chunk.map_err(LlmHubError::from)?;
+                reporter.metrics.response_bytes += chunk.len();
+                Ok(chunk)
+            })
+            .boxed())
+    }
+
+    /// Sends a non-streaming chat request and returns the first choice's
+    /// text, or an `ApiError` when the response carries none.
+    pub async fn chat_text(&self, request: &ApiRequest) -> Result<String> {
+        let response = self.chat(request).await?;
+        response
+            .first_choice()
+            .and_then(|choice| choice.message.text())
+            .map(|text| text.into_owned())
+            .ok_or_else(|| {
+                LlmHubError::ApiError(format!("Response {} has no content", response.id))
+            })
+    }
+
+    /// Sends a streaming chat request and yields only the first choice's
+    /// content deltas; role-only, reasoning and tool-call deltas are skipped.
+    pub fn chat_stream_text(
+        &self,
+        request: &ApiRequest,
+    ) -> Result<BoxStream<'static, Result<String>>> {
+        let stream = self.chat_stream(request)?.filter_map(|chunk| async move {
+            match chunk {
+                Ok(chunk) => chunk
+                    .choices
+                    .into_iter()
+                    .find(|choice| choice.index == 0)
+                    .and_then(|choice| choice.delta.content)
+                    .filter(|content| !content.is_empty())
+                    .map(Ok),
+                Err(e) => Some(Err(e)),
+            }
+        });
+        Ok(stream.boxed())
+    }
+
+    /// Sends a streaming chat request and folds the whole stream.
+    ///
+    /// A stream that ends without any content or finish reason (typically an
+    /// immediate `[DONE]` after a backend hiccup) is retried up to
+    /// `empty_retries` times; pass `0` to disable the guard.
+    pub async fn chat_stream_collect(
+        &self,
+        request: &ApiRequest,
+        empty_retries: usize,
+    ) -> Result<StreamAccumulator> {
+        let mut attempt = 0;
+        loop {
+            let mut stream = self.chat_stream(request)?;
+            let mut accumulator = StreamAccumulator::new();
+            while let Some(chunk) = stream.next().await {
+                accumulator.push(&chunk?);
+            }
+            if !accumulator.is_empty() || attempt >= empty_retries {
+                return Ok(accumulator);
+            }
+            attempt += 1;
+            log::warn!(
+                "Stream ended without content, retrying ({}/{})",
+                attempt,
+                empty_retries
+            );
+        }
+    }
+
+    /// Posts `body` to the `api_type` endpoint and decodes each server-sent
+    /// event as `T`, until `[DONE]` or the end of the stream.
+    ///
+    /// The response body is held only by the returned stream, so dropping the
+    /// stream aborts the request and releases the connection.
+    ///
+    /// Shared by every streaming endpoint, so BOM/prefix stripping, keep-alive
+    /// skipping, the SSE line limit, rate limiting and the concurrency cap
+    /// behave identically.
+    fn stream_sse<T: DeserializeOwned + Send + 'static>(
+        &self,
+        provider: ApiProvider,
+        api_type: ApiType,
+        body: &impl Serialize,
+        headers: &HashMap<String, String>,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<T>> + Send>>> {
+        let builder = with_request_headers(provider, self.post(provider, api_type, true)?, headers);
+        let (builder, request_bytes) = json_body(builder, body)?;
+        let reporter = self.metrics_reporter(provider, api_type, true, request_bytes);
+        let rate_limiter = self.rate_limiter.clone();
+        let concurrency = self.concurrency.clone();
+        let max_sse_line_bytes = self.max_sse_line_bytes;
+
+        let stream = async_stream::stream! {
+            let mut reporter = reporter;
+            let _permit = acquire_permit(&concurrency).await;
+            rate_limiter.acquire(provider).await;
+            let response = match builder.send().await {
+                Ok(response) => response,
+                Err(e) => {
+                    yield Err(LlmHubError::from(e));
+                    return;
+                }
+            };
+            if !response.status().is_success() {
+                yield Err(error_from_response(response).await);
+                return;
+            }
+
+            let mut decoder = SseDecoder::new(max_sse_line_bytes);
+            let mut body = response.bytes_stream();
+            let mut finished = false;
+            while !finished {
+                let events = match body.next().await {
+                    Some(Ok(bytes)) => {
+                        reporter.metrics.response_bytes += bytes.len();
+                        match decoder.feed(&bytes) {
+                            Ok(events) => events,
                             Err(e) => {
-                                yield Err(LlmHubError::SerializationError(e));
-                                continue;
+                                yield Err(e);
+                                return;
                             }
-                        };
-                        yield Ok(chunk);
+                        }
+                    }
+                    Some(Err(e)) => {
+                        yield Err(LlmHubError::from(e));
+                        return;
                     }
-                    Err(e) => {
-                        es.close();
-                        yield Err(LlmHubError::StreamError(e.to_string()));
-                        break;
+                    None => {
+                        finished = true;
+                        decoder.finish().into_iter().collect()
+                    }
+                };
+                for event in events {
+                    let data = strip_sse_prefix(&event);
+                    if data == "[DONE]" {
+                        return;
+                    }
+                    if is_keepalive(data) {
+                        continue;
+                    }
+                    match serde_json::from_str::<T>(data) {
+                        Ok(item) => yield Ok(item),
+                        Err(e) => yield Err(LlmHubError::SerializationError(e)),
                     }
                 }
             }
@@ -97,3 +1324,235 @@ impl Client {
         Ok(Box::pin(stream))
     }
 }
+
+/// The combined result of [`list_all_models`].
+#[derive(Debug, Default)]
+pub struct ModelCatalog {
+    pub models: Vec<(ApiProvider, ModelInfo)>,
+    /// Providers whose listing failed, with the reason.
+    pub errors: Vec<(ApiProvider, LlmHubError)>,
+}
+
+/// Lists the models of every configured provider that has a models
+/// endpoint, querying them concurrently.
+///
+/// A provider that fails (or has no API key) is reported in
+/// [`ModelCatalog::errors`] without affecting the others.
+pub async fn list_all_models(configs: &[ProviderConfig]) -> ModelCatalog {
+    let listings = configs
+        .iter()
+        .filter(|config| {
+            config
+                .api_provider
+                .get_endpoint_config()
+                .supported_types
+                .contains(&ApiType::ListModels)
+        })
+        .map(|config| async move {
+            let provider = config.api_provider;
+            let result = match config.to_client() {
+                Ok(client) => client.list_models(provider).await,
+                Err(e) => Err(e),
+            };
+            (provider, result)
+        });
+
+    let mut catalog = ModelCatalog::default();
+    for (provider, result) in futures::future::join_all(listings).await {
+        match result {
+            Ok(models) => catalog
+                .models
+                .extend(models.into_iter().map(|model| (provider, model))),
+            Err(e) => catalog.errors.push((provider, e)),
+        }
+    }
+    catalog
+}
+
+/// Converts a non-success response into an error.
+///
+/// A 429 becomes `RateLimitError` carrying the advised wait, unless its body
+/// reports an exhausted quota, which waiting won't fix; that and other
+/// statuses become `ApiErrorResponse` when the error envelope is recognized
+/// and `ApiError` otherwise.
+async fn error_from_response(response: Response) -> LlmHubError {
+    let status = response.status();
+    let retry_after = retry_after_secs(response.headers());
+    let error_text = response
+        .text()
+        .await
+        .unwrap_or_else(|_| "Unknown API error".to_string());
+    match ApiErrorDetails::parse(status.as_u16(), &error_text) {
+        Some(details) if details.is_quota_exhausted() => LlmHubError::ApiErrorResponse(details),
+        _ if status == StatusCode::TOO_MANY_REQUESTS => LlmHubError::RateLimitError(retry_after),
+        Some(details) => LlmHubError::ApiErrorResponse(details),
+        None => LlmHubError::ApiError(error_text),
+    }
+}
+
+/// Wait used when a 429 response carries no usable `Retry-After` header.
+const DEFAULT_RETRY_AFTER_SECS: u64 = 1;
+
+/// Reads a `Retry-After` header given either as delay seconds or as an
+/// HTTP date, falling back to [`DEFAULT_RETRY_AFTER_SECS`].
+fn retry_after_secs(headers: &HeaderMap) -> u64 {
+    let Some(value) = headers.get(RETRY_AFTER).and_then(|v| v.to_str().ok()) else {
+        return DEFAULT_RETRY_AFTER_SECS;
+    };
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return secs;
+    }
+    match httpdate::parse_http_date(value) {
+        Ok(at) => at
+            .duration_since(std::time::SystemTime::now())
+            .map_or(0, |wait| wait.as_secs_f64().ceil() as u64),
+        Err(_) => DEFAULT_RETRY_AFTER_SECS,
+    }
+}
+
+/// Adds per-request headers, skipping any that would replace the client's
+/// credentials (`Authorization` or the provider's API key header).
+fn with_request_headers(
+    provider: ApiProvider,
+    mut builder: RequestBuilder,
+    headers: &HashMap<String, String>,
+) -> RequestBuilder {
+    for (key, value) in headers {
+        let is_credential = key.eq_ignore_ascii_case(AUTHORIZATION.as_str())
+            || matches!(provider.auth_scheme(), AuthScheme::Header(name) if key.eq_ignore_ascii_case(name));
+        if is_credential {
+            log::warn!(
+                "Ignoring request header {} that would replace the API key",
+                key
+            );
+            continue;
+        }
+        builder = builder.header(key, value);
+    }
+    builder
+}
+
+/// Attaches `body` as JSON, returning the builder and the body's length.
+fn json_body(builder: RequestBuilder, body: &impl Serialize) -> Result<(RequestBuilder, usize)> {
+    let bytes = serde_json::to_vec(body)?;
+    let len = bytes.len();
+    Ok((
+        builder.header(CONTENT_TYPE, "application/json").body(bytes),
+        len,
+    ))
+}
+
+/// Waits for an in-flight slot when a concurrency cap is configured.
+async fn acquire_permit(concurrency: &Option<Arc<Semaphore>>) -> Option<OwnedSemaphorePermit> {
+    match concurrency {
+        Some(semaphore) => semaphore.clone().acquire_owned().await.ok(),
+        None => None,
+    }
+}
+
+/// Returns a `reqwest` client builder using the TLS backend selected by the
+/// `rustls-tls` (preferred when both are enabled) or `native-tls` feature.
+fn http_client_builder() -> reqwest::ClientBuilder {
+    let builder = ReqwestClient::builder();
+    #[cfg(feature = "rustls-tls")]
+    let builder = builder.use_rustls_tls();
+    #[cfg(all(feature = "native-tls", not(feature = "rustls-tls")))]
+    let builder = builder.use_native_tls();
+    builder
+}
+
+/// Strips framing that some gateways leave in the event payload: a leading BOM
+/// and a duplicated `data:` prefix, with or without the trailing space.
+fn strip_sse_prefix(data: &str) -> &str {
+    let data = data.trim_start_matches('\u{feff}');
+    match data.strip_prefix("data:") {
+        Some(rest) => rest.strip_prefix(' ').unwrap_or(rest),
+        None => data,
+    }
+}
+
+/// Detects heartbeat payloads that some providers interleave with real
+/// chunks: blank data, or a JSON object carrying neither `id` nor `choices`.
+/// Objects with an `error` key, and typed events such as Anthropic's, are
+/// never treated as heartbeats.
+fn is_keepalive(data: &str) -> bool {
+    if data.trim().is_empty() {
+        return true;
+    }
+    match serde_json::from_str::<serde_json::Value>(data) {
+        Ok(serde_json::Value::Object(map)) => !["id", "choices", "error", "type"]
+            .iter()
+            .any(|key| map.contains_key(*key)),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn with_rate_limit_leaves_earlier_clones_untouched() {
+        let original = Client::new("key".into());
+        let limited = original.clone().with_rate_limit(ApiProvider::OpenAI, 10);
+
+        assert_eq!(
+            original
+                .rate_limiter
+                .requests_per_minute(ApiProvider::OpenAI),
+            None
+        );
+        assert_eq!(
+            limited
+                .rate_limiter
+                .requests_per_minute(ApiProvider::OpenAI),
+            Some(10)
+        );
+    }
+
+    #[test]
+    fn zero_max_concurrency_means_unlimited() {
+        let client = Client::new("key".into()).with_max_concurrency(0);
+        assert!(client.concurrency.is_none());
+
+        let client = Client::builder().max_concurrency(2).build().unwrap();
+        let permits = client.concurrency.as_ref().map(|s| s.available_permits());
+        assert_eq!(permits, Some(2));
+    }
+
+    fn retry_after(value: &str) -> HeaderMap {
+        HeaderMap::from_iter([(RETRY_AFTER, value.parse().unwrap())])
+    }
+
+    #[test]
+    fn retry_after_accepts_seconds() {
+        assert_eq!(retry_after_secs(&retry_after("7")), 7);
+        assert_eq!(retry_after_secs(&retry_after(" 30 ")), 30);
+    }
+
+    #[test]
+    fn retry_after_accepts_an_http_date() {
+        let at = std::time::SystemTime::now() + Duration::from_secs(120);
+        let wait = retry_after_secs(&retry_after(&httpdate::fmt_http_date(at)));
+        assert!((119..=120).contains(&wait), "waited {}", wait);
+
+        let past = std::time::SystemTime::now() - Duration::from_secs(60);
+        assert_eq!(
+            retry_after_secs(&retry_after(&httpdate::fmt_http_date(past))),
+            0
+        );
+    }
+
+    #[test]
+    fn retry_after_falls_back_when_missing_or_invalid() {
+        assert_eq!(
+            retry_after_secs(&HeaderMap::new()),
+            DEFAULT_RETRY_AFTER_SECS
+        );
+        assert_eq!(
+            retry_after_secs(&retry_after("soon")),
+            DEFAULT_RETRY_AFTER_SECS
+        );
+    }
+}
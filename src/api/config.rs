@@ -1,5 +1,7 @@
+use super::client::Client;
 use super::providers::ApiProvider;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Configuration settings for API providers
 ///
@@ -7,11 +9,50 @@ use serde::{Deserialize, Serialize};
 /// - `api_provider`: Enum variant specifying the AI service provider
 /// - `api_base_url`: Optional base URL for API endpoints (can override default provider URLs)
 /// - `api_key`: Authentication credential for the API service
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// - `extra_headers`: Headers sent with every request to this provider
+/// - `requests_per_minute`: Client-side rate limit for this provider; unthrottled when unset
+#[derive(Serialize, Deserialize, Clone)]
 pub struct ProviderConfig {
     pub api_provider: ApiProvider,
     pub api_base_url: Option<String>,
     pub api_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra_headers: Option<HashMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_minute: Option<u32>,
+}
+
+impl std::fmt::Debug for ProviderConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let extra_headers = self.extra_headers.as_ref().map(|headers| {
+            headers
+                .iter()
+                .map(|(key, value)| {
+                    let value = if is_secret_header(key) {
+                        "<redacted>"
+                    } else {
+                        value.as_str()
+                    };
+                    (key.as_str(), value)
+                })
+                .collect::<HashMap<_, _>>()
+        });
+        f.debug_struct("ProviderConfig")
+            .field("api_provider", &self.api_provider)
+            .field("api_base_url", &self.api_base_url)
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .field("extra_headers", &extra_headers)
+            .field("requests_per_minute", &self.requests_per_minute)
+            .finish()
+    }
+}
+
+/// Returns true for header names that usually carry credentials.
+fn is_secret_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    ["auth", "key", "token", "secret", "cookie"]
+        .iter()
+        .any(|marker| name.contains(marker))
 }
 
 impl ProviderConfig {
@@ -30,9 +71,17 @@ impl ProviderConfig {
             api_provider,
             api_base_url,
             api_key,
+            extra_headers: None,
+            requests_per_minute: None,
         }
     }
 
+    /// Builds a `Client` from this configuration's API key, base URL, extra
+    /// headers and rate limit
+    pub fn to_client(&self) -> crate::Result<Client> {
+        Client::from_config(self.clone())
+    }
+
     /// Loads provider configurations from a file, creating default config if file doesn't exist
     /// Also merges configurations from environment variables
     pub fn load_from_file(path: &str) -> Result<Vec<Self>, Box<dyn std::error::Error>> {
@@ -46,22 +95,15 @@ impl ProviderConfig {
 
         let config_content = std::fs::read_to_string(path)?;
         let mut configs: Vec<Self> = serde_json::from_str(&config_content)?;
+        Self::merge_env(&mut configs);
 
-        for provider in [
-            ApiProvider::OpenAI,
-            ApiProvider::Anthropic,
-            ApiProvider::Deepseek,
-            ApiProvider::Siliconflow,
-            ApiProvider::Qianfan,
-            ApiProvider::ZhipuAI,
-            ApiProvider::Volcengine,
-            ApiProvider::XAI,
-            ApiProvider::Tencent,
-            ApiProvider::ALIBAILIAN,
-            ApiProvider::GOOGLE,
-        ]
-        .iter()
-        {
+        Ok(configs)
+    }
+
+    /// Overrides configurations with the providers' environment variables,
+    /// adding a configuration for any provider that is only set in the environment
+    pub fn merge_env(configs: &mut Vec<Self>) {
+        for provider in ApiProvider::ALL.iter() {
             if let Some(env_config) = Self::from_env(*provider) {
                 if let Some(existing) = configs.iter_mut().find(|c| c.api_provider == *provider) {
                     existing.api_key = env_config.api_key;
@@ -71,8 +113,6 @@ impl ProviderConfig {
                 }
             }
         }
-
-        Ok(configs)
     }
 
     /// Creates a default configuration file with preset providers
@@ -86,46 +126,64 @@ impl ProviderConfig {
                 api_provider: ApiProvider::OpenAI,
                 api_base_url: Some(ApiProvider::OpenAI.base_url().to_string()),
                 api_key: Some("your_openai_key_here".to_string()),
+                extra_headers: None,
+                requests_per_minute: None,
             },
             Self {
                 api_provider: ApiProvider::Anthropic,
                 api_base_url: Some(ApiProvider::Anthropic.base_url().to_string()),
                 api_key: Some("your_anthropic_key_here".to_string()),
+                extra_headers: None,
+                requests_per_minute: None,
             },
             Self {
                 api_provider: ApiProvider::Tencent,
                 api_base_url: Some(ApiProvider::Tencent.base_url().to_string()),
                 api_key: Some("your_TencentTencent_key_here".to_string()),
+                extra_headers: None,
+                requests_per_minute: None,
             },
             Self {
                 api_provider: ApiProvider::Qianfan,
                 api_base_url: Some(ApiProvider::Qianfan.base_url().to_string()),
                 api_key: Some("your_qianfan_key_here".to_string()),
+                extra_headers: None,
+                requests_per_minute: None,
             },
             Self {
                 api_provider: ApiProvider::Siliconflow,
                 api_base_url: Some(ApiProvider::Siliconflow.base_url().to_string()),
                 api_key: Some("your_siliconflow_key_here".to_string()),
+                extra_headers: None,
+                requests_per_minute: None,
             },
             Self {
                 api_provider: ApiProvider::Deepseek,
                 api_base_url: Some(ApiProvider::Deepseek.base_url().to_string()),
                 api_key: Some("your_deepseek_key_here".to_string()),
+                extra_headers: None,
+                requests_per_minute: None,
             },
             Self {
                 api_provider: ApiProvider::ZhipuAI,
                 api_base_url: Some(ApiProvider::ZhipuAI.base_url().to_string()),
                 api_key: Some("your_zhipuai_key_here".to_string()),
+                extra_headers: None,
+                requests_per_minute: None,
             },
             Self {
                 api_provider: ApiProvider::Volcengine,
                 api_base_url: Some(ApiProvider::Volcengine.base_url().to_string()),
                 api_key: Some("your_volcengine_key_here".to_string()),
+                extra_headers: None,
+                requests_per_minute: None,
             },
             Self {
                 api_provider: ApiProvider::XAI,
                 api_base_url: Some(ApiProvider::XAI.base_url().to_string()),
                 api_key: Some("your_XAI_key_here".to_string()),
+                extra_headers: None,
+                requests_per_minute: None,
             },
         ];
 
@@ -139,19 +197,33 @@ impl ProviderConfig {
         configs.iter().find(|c| c.api_provider == provider)
     }
 
-    /// Returns the default path for the configuration file
+    /// Returns the default path for the configuration file, `llmhub.json`
+    /// inside [`ProviderConfig::config_dir`]
     pub fn default_config_path() -> String {
-        let home_dir = dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
-        home_dir
-            .join(".config/llmhub.json")
+        Self::config_dir()
+            .join("llmhub.json")
             .to_string_lossy()
             .to_string()
     }
 
+    /// Resolves the configuration directory from, in order of precedence,
+    /// `$LLMHUB_CONFIG_DIR`, `$XDG_CONFIG_HOME`, and `~/.config`
+    pub fn config_dir() -> std::path::PathBuf {
+        ["LLMHUB_CONFIG_DIR", "XDG_CONFIG_HOME"]
+            .iter()
+            .filter_map(std::env::var_os)
+            .find(|dir| !dir.is_empty())
+            .map(std::path::PathBuf::from)
+            .unwrap_or_else(|| {
+                dirs::home_dir()
+                    .unwrap_or_else(|| std::path::PathBuf::from("."))
+                    .join(".config")
+            })
+    }
+
     /// Ensures the configuration directory exists, creating it if necessary
     pub fn ensure_config_dir() -> Result<(), Box<dyn std::error::Error>> {
-        let home_dir = dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
-        let config_dir = home_dir.join(".config");
+        let config_dir = Self::config_dir();
 
         if !config_dir.exists() {
             std::fs::create_dir_all(&config_dir)?;
@@ -160,6 +232,14 @@ impl ProviderConfig {
         Ok(())
     }
 
+    /// Sets a header sent with every request to this provider
+    pub fn set_extra_header(&mut self, key: String, value: String) -> &mut Self {
+        self.extra_headers
+            .get_or_insert_with(HashMap::new)
+            .insert(key, value);
+        self
+    }
+
     /// Sets the API key for this provider configuration
     pub fn set_api_key(&mut self, api_key: String) -> &mut Self {
         self.api_key = Some(api_key);
@@ -179,36 +259,22 @@ impl ProviderConfig {
 
     /// Creates a provider configuration from environment variables
     pub fn from_env(provider: ApiProvider) -> Option<Self> {
-        let env_prefix = match provider {
-            ApiProvider::OpenAI => "OPENAI",
-            ApiProvider::Anthropic => "ANTHROPIC",
-            ApiProvider::Deepseek => "DEEPSEEK",
-            ApiProvider::Siliconflow => "SILICONFLOW",
-            ApiProvider::Qianfan => "QIANFAN",
-            ApiProvider::ZhipuAI => "ZHIPUAI",
-            ApiProvider::Volcengine => "VOLCENGINE",
-            ApiProvider::XAI => "XAI",
-            ApiProvider::Tencent => "TENCENT",
-            ApiProvider::ALIBAILIAN => "ALIBAILIAN",
-            ApiProvider::GOOGLE => "GOOGLE",
-        };
+        let env_prefix = provider.env_prefix();
 
         let api_key_var = format!("{}_API_KEY", env_prefix);
         let api_base_url_var = format!("{}_API_BASE", env_prefix);
 
-        let api_key = std::env::var(&api_key_var).ok();
+        let api_key = Some(std::env::var(&api_key_var).ok()?);
         let api_base_url = std::env::var(&api_base_url_var)
             .ok()
             .or_else(|| Some(provider.base_url().to_string()));
 
-        if api_key.is_none() {
-            return None;
-        }
-
         Some(Self {
             api_provider: provider,
             api_base_url,
             api_key,
+            extra_headers: None,
+            requests_per_minute: None,
         })
     }
 }
@@ -0,0 +1,101 @@
+use crate::api::client::Client;
+use crate::api::message::Message;
+use crate::api::request::{ApiRequest, RequestOptions};
+use crate::api::session::Session;
+use crate::api::stream::StreamAccumulator;
+use crate::models::models::Model;
+use crate::utils::error::Result;
+use futures::StreamExt;
+use futures::stream::BoxStream;
+
+/// A stateful multi-turn chat with one model, created by
+/// [`Client::conversation`].
+///
+/// Each turn is sent with the full history, and the user message and the
+/// assistant reply are appended to the session only once the turn succeeds.
+pub struct Conversation {
+    client: Client,
+    model: Model,
+    options: RequestOptions,
+    session: Session,
+}
+
+impl Conversation {
+    pub fn new(client: Client, model: Model, options: RequestOptions) -> Self {
+        Self {
+            client,
+            model,
+            options,
+            session: Session::new(),
+        }
+    }
+
+    /// Continues an existing session instead of starting an empty one.
+    pub fn with_session(mut self, session: Session) -> Self {
+        self.session = session;
+        self
+    }
+
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    pub fn into_session(self) -> Session {
+        self.session
+    }
+
+    /// Sends `user_input` and returns the assistant's reply.
+    pub async fn send(&mut self, user_input: impl Into<String>) -> Result<String> {
+        let user = Message::user(user_input);
+        let request = self.request(user.clone());
+        let response = self.client.chat(&request).await?;
+        let reply = response.content().unwrap_or_default().to_string();
+        self.session.add_message(user);
+        self.session.append_response(&response);
+        Ok(reply)
+    }
+
+    /// Sends `user_input` and streams the reply's content deltas.
+    ///
+    /// The turn is recorded when the stream ends without error; dropping the
+    /// stream early discards it.
+    pub fn send_stream(
+        &mut self,
+        user_input: impl Into<String>,
+    ) -> Result<BoxStream<'_, Result<String>>> {
+        let user = Message::user(user_input);
+        let request = self.request(user.clone()).stream(true);
+        let mut chunks = self.client.chat_stream(&request)?;
+
+        let stream = async_stream::stream! {
+            let mut accumulator = StreamAccumulator::new();
+            while let Some(chunk) = chunks.next().await {
+                match chunk {
+                    Ok(chunk) => {
+                        accumulator.push(&chunk);
+                        let delta = chunk
+                            .first_choice()
+                            .and_then(|choice| choice.delta.content.clone());
+                        if let Some(delta) = delta {
+                            yield Ok(delta);
+                        }
+                    }
+                    Err(e) => {
+                        yield Err(e);
+                        return;
+                    }
+                }
+            }
+            let reply = accumulator.content(0).unwrap_or_default().to_string();
+            self.session.add_message(user);
+            self.session.add_message(Message::assistant(reply));
+        };
+        Ok(stream.boxed())
+    }
+
+    fn request(&self, user: Message) -> ApiRequest {
+        ApiRequest::new(self.model.clone(), Some(&self.session))
+            .with_options(self.options.clone())
+            .add_message(user)
+    }
+}
@@ -0,0 +1,109 @@
+use crate::api::response::Usage;
+use crate::models::models::Model;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize};
+
+/// What to do with an input longer than the embedding model accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OversizePolicy {
+    /// Fail with a `ConfigError` before sending the request.
+    Error,
+    /// Keep only the leading part that fits.
+    Truncate,
+    /// Embed the input in chunks that fit and average their vectors, so each
+    /// input still yields a single unit-length embedding.
+    Split,
+}
+
+/// The request body for the embeddings endpoint.
+#[derive(Debug, Serialize, Clone)]
+pub struct EmbeddingRequest {
+    pub model: Model,
+    pub input: Vec<String>,
+    /// Applied by the client to inputs over the model's
+    /// [`max_embedding_input_tokens`](Model::max_embedding_input_tokens);
+    /// `None` sends inputs as-is.
+    #[serde(skip)]
+    pub oversize_policy: Option<OversizePolicy>,
+    /// Number of dimensions to truncate the embeddings to, where supported.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding_format: Option<EncodingFormat>,
+}
+
+/// How the provider encodes embedding vectors on the wire. Both are decoded
+/// into `Vec<f32>`; `Base64` roughly halves the response size.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EncodingFormat {
+    Float,
+    Base64,
+}
+
+impl EmbeddingRequest {
+    pub fn new(model: Model, input: Vec<String>) -> Self {
+        Self {
+            model,
+            input,
+            oversize_policy: None,
+            dimensions: None,
+            encoding_format: None,
+        }
+    }
+
+    /// Requests embeddings with `dimensions` dimensions.
+    pub fn with_dimensions(mut self, dimensions: u32) -> Self {
+        self.dimensions = Some(dimensions);
+        self
+    }
+
+    /// Sets the wire encoding of the returned vectors.
+    pub fn with_encoding_format(mut self, format: EncodingFormat) -> Self {
+        self.encoding_format = Some(format);
+        self
+    }
+
+    /// Sets how inputs exceeding the model's input limit are handled.
+    pub fn with_oversize_policy(mut self, policy: OversizePolicy) -> Self {
+        self.oversize_policy = Some(policy);
+        self
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct EmbeddingData {
+    pub index: usize,
+    #[serde(deserialize_with = "deserialize_embedding")]
+    pub embedding: Vec<f32>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct EmbeddingResponse {
+    pub data: Vec<EmbeddingData>,
+    pub model: Option<String>,
+    pub usage: Option<Usage>,
+}
+
+/// Accepts a JSON array of floats or a base64 string of little-endian `f32`s.
+fn deserialize_embedding<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f32>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Encoded {
+        Float(Vec<f32>),
+        Base64(String),
+    }
+
+    match Encoded::deserialize(deserializer)? {
+        Encoded::Float(values) => Ok(values),
+        Encoded::Base64(encoded) => {
+            let bytes = STANDARD.decode(encoded).map_err(de::Error::custom)?;
+            Ok(bytes
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect())
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use crate::api::client::Client;
+use crate::models::models::Model;
+use crate::utils::error::{LlmHubError, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// How generated images are returned.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageResponseFormat {
+    Url,
+    B64Json,
+}
+
+/// The request body for the image generation endpoint.
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Serialize, Clone)]
+pub struct ImageRequest {
+    pub model: Model,
+    pub prompt: String,
+    /// Number of images to generate.
+    pub n: Option<u32>,
+    /// Image size such as `"1024x1024"`.
+    pub size: Option<String>,
+    pub response_format: Option<ImageResponseFormat>,
+}
+
+impl ImageRequest {
+    pub fn new(model: Model, prompt: impl Into<String>) -> Self {
+        Self {
+            model,
+            prompt: prompt.into(),
+            n: None,
+            size: None,
+            response_format: None,
+        }
+    }
+
+    pub fn with_n(mut self, n: u32) -> Self {
+        self.n = Some(n);
+        self
+    }
+
+    pub fn with_size(mut self, size: impl Into<String>) -> Self {
+        self.size = Some(size.into());
+        self
+    }
+
+    pub fn with_response_format(mut self, format: ImageResponseFormat) -> Self {
+        self.response_format = Some(format);
+        self
+    }
+}
+
+/// A generated image, returned either as a URL or inline as base64.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ImageObject {
+    pub url: Option<String>,
+    pub b64_json: Option<String>,
+    pub revised_prompt: Option<String>,
+}
+
+/// The response body of the image generation endpoint.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ImageResponse {
+    pub created: Option<i64>,
+    pub data: Vec<ImageObject>,
+}
+
+impl ImageResponse {
+    /// Writes every image to `dir/prefix-N.<ext>`, numbering from 1, and
+    /// returns the paths in response order.
+    ///
+    /// Base64 entries are decoded and URL entries downloaded through
+    /// `client`; the extension is inferred from the image data (`png`,
+    /// `jpg`, `webp` or `gif`, falling back to `png`).
+    pub async fn save_all(
+        &self,
+        client: &Client,
+        dir: impl AsRef<Path>,
+        prefix: &str,
+    ) -> Result<Vec<PathBuf>> {
+        let dir = dir.as_ref();
+        tokio::fs::create_dir_all(dir).await?;
+        let mut paths = Vec::with_capacity(self.data.len());
+        for (i, image) in self.data.iter().enumerate() {
+            let bytes = image.bytes(client).await?;
+            let path = dir.join(format!("{}-{}.{}", prefix, i + 1, image_extension(&bytes)));
+            tokio::fs::write(&path, &bytes).await?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+}
+
+impl ImageObject {
+    /// Returns the image data, decoding `b64_json` or downloading `url`.
+    pub async fn bytes(&self, client: &Client) -> Result<Bytes> {
+        if let Some(b64) = &self.b64_json {
+            return Ok(Bytes::from(STANDARD.decode(b64)?));
+        }
+        match &self.url {
+            Some(url) => client.download(url).await,
+            None => Err(LlmHubError::ApiError(
+                "Image has neither url nor b64_json".to_string(),
+            )),
+        }
+    }
+}
+
+/// Infers a file extension from an image's magic bytes.
+fn image_extension(bytes: &[u8]) -> &'static str {
+    if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+        "jpg"
+    } else if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+        "webp"
+    } else if bytes.starts_with(b"GIF8") {
+        "gif"
+    } else {
+        "png"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn save_all_decodes_base64_images_in_order() {
+        let png = b"\x89PNG\r\n\x1a\nfirst".to_vec();
+        let jpg = b"\xff\xd8\xff\xe0second".to_vec();
+        let response: ImageResponse = serde_json::from_value(serde_json::json!({
+            "created": 1,
+            "data": [
+                { "b64_json": STANDARD.encode(&png) },
+                { "b64_json": STANDARD.encode(&jpg) }
+            ]
+        }))
+        .unwrap();
+        let dir = std::env::temp_dir().join(format!("llmhub-{}", uuid::Uuid::new_v4().simple()));
+
+        let paths = response
+            .save_all(&Client::new("key".into()), &dir, "cat")
+            .await
+            .unwrap();
+
+        assert_eq!(paths, [dir.join("cat-1.png"), dir.join("cat-2.jpg")]);
+        assert_eq!(std::fs::read(&paths[0]).unwrap(), png);
+        assert_eq!(std::fs::read(&paths[1]).unwrap(), jpg);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
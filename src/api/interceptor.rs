@@ -0,0 +1,58 @@
+use crate::api::providers::{ApiProvider, ApiType};
+use crate::api::request::ApiRequest;
+use crate::api::response::{ApiResponse, StreamChunk};
+
+/// A hook applied to every request a `Client` sends.
+///
+/// Interceptors run in registration order, and every method defaults to a
+/// no-op so implementations only override what they need.
+pub trait Interceptor: Send + Sync {
+    /// Mutates the outgoing request before it is serialized, e.g. to inject a
+    /// guardrail system prompt, clamp `max_tokens`, or redact PII.
+    fn transform_request(&self, _request: &mut ApiRequest) {}
+
+    /// Mutates a non-streaming response before it is returned to the caller,
+    /// e.g. to strip a leaked system prompt or enforce a maximum length.
+    fn on_response(&self, _response: &mut ApiResponse) {}
+
+    /// Mutates each streamed chunk before it is yielded, so redaction applies
+    /// live to content deltas.
+    ///
+    /// This runs once per chunk on the streaming path, so keep it cheap. A
+    /// pattern may also be split across deltas; filters that must see whole
+    /// words need to buffer state themselves.
+    fn on_stream_chunk(&self, _chunk: &mut StreamChunk) {}
+
+    /// Observes the wire size of a finished request, for capacity planning.
+    ///
+    /// Called once per HTTP request, including failed ones and streams that
+    /// are dropped early, after which `response_bytes` counts what was read.
+    fn on_metrics(&self, _metrics: &RequestMetrics) {}
+}
+
+/// Wire sizes of one request, reported through [`Interceptor::on_metrics`].
+#[derive(Debug, Clone)]
+pub struct RequestMetrics {
+    pub provider: ApiProvider,
+    pub api_type: ApiType,
+    pub streaming: bool,
+    /// Length of the serialized request body.
+    pub request_bytes: usize,
+    /// Length of the response body, summed over chunks when streaming.
+    pub response_bytes: usize,
+}
+
+/// Reports its metrics to every interceptor when dropped, so streams that
+/// end early are still accounted for.
+pub(crate) struct MetricsReporter {
+    pub(crate) interceptors: Vec<std::sync::Arc<dyn Interceptor>>,
+    pub(crate) metrics: RequestMetrics,
+}
+
+impl Drop for MetricsReporter {
+    fn drop(&mut self) {
+        for interceptor in &self.interceptors {
+            interceptor.on_metrics(&self.metrics);
+        }
+    }
+}
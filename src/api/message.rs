@@ -1,5 +1,6 @@
 use crate::api::response::ToolCall;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 
 /// Enum representing different roles in a conversation
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -16,7 +17,7 @@ pub enum Role {
 pub struct Message {
     pub role: Role,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub content: Option<String>,
+    pub content: Option<Content>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<ToolCall>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -28,12 +29,31 @@ impl Message {
     pub fn new(role: Role, content: impl Into<String>) -> Self {
         Self {
             role,
-            content: Some(content.into()),
+            content: Some(Content::Text(content.into())),
             tool_calls: None,
             tool_call_id: None,
         }
     }
 
+    /// Creates a user message with text followed by an image, given as an
+    /// `http(s)` URL or a `data:` URL (see [`ContentPart::image_base64`]).
+    pub fn user_with_image(text: impl Into<String>, image_url: impl Into<String>) -> Self {
+        Self {
+            role: Role::User,
+            content: Some(Content::Parts(vec![
+                ContentPart::text(text),
+                ContentPart::image_url(image_url),
+            ])),
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+
+    /// Returns the message text, joining the text parts of multimodal content.
+    pub fn text(&self) -> Option<Cow<'_, str>> {
+        self.content.as_ref().map(Content::text)
+    }
+
     /// Creates a system-level instruction message
     pub fn system(content: impl Into<String>) -> Self {
         Self::new(Role::System, content)
@@ -63,9 +83,150 @@ impl Message {
     pub fn tool(content: impl Into<String>, tool_call_id: impl Into<String>) -> Self {
         Self {
             role: Role::Tool,
-            content: Some(content.into()),
+            content: Some(Content::Text(content.into())),
             tool_calls: None,
             tool_call_id: Some(tool_call_id.into()),
         }
     }
 }
+
+/// The content of a message: plain text, or an array of parts for
+/// multimodal input. Serializes as a string or an array respectively.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Content {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+impl Content {
+    /// Returns the text of a `Text` content.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Content::Text(text) => Some(text),
+            Content::Parts(_) => None,
+        }
+    }
+
+    /// Returns the text, joining the text parts of `Parts` with newlines.
+    pub fn text(&self) -> Cow<'_, str> {
+        match self {
+            Content::Text(text) => Cow::Borrowed(text),
+            Content::Parts(parts) => Cow::Owned(
+                parts
+                    .iter()
+                    .filter_map(|part| match part {
+                        ContentPart::Text { text } => Some(text.as_str()),
+                        ContentPart::ImageUrl { .. } => None,
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+        }
+    }
+
+    /// Returns mutable references to every piece of text in the content.
+    pub fn texts_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            Content::Text(text) => vec![text],
+            Content::Parts(parts) => parts
+                .iter_mut()
+                .filter_map(|part| match part {
+                    ContentPart::Text { text } => Some(text),
+                    ContentPart::ImageUrl { .. } => None,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<String> for Content {
+    fn from(text: String) -> Self {
+        Content::Text(text)
+    }
+}
+
+impl From<&str> for Content {
+    fn from(text: &str) -> Self {
+        Content::Text(text.to_string())
+    }
+}
+
+/// One part of a multimodal message.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    Text { text: String },
+    ImageUrl { image_url: ImageUrl },
+}
+
+impl ContentPart {
+    pub fn text(text: impl Into<String>) -> Self {
+        ContentPart::Text { text: text.into() }
+    }
+
+    /// An image referenced by an `http(s)` or `data:` URL.
+    pub fn image_url(url: impl Into<String>) -> Self {
+        ContentPart::ImageUrl {
+            image_url: ImageUrl {
+                url: url.into(),
+                detail: None,
+            },
+        }
+    }
+
+    /// An inline image, sent as a `data:` URL. `data` is the base64-encoded
+    /// image and `mime_type` e.g. `image/png`.
+    pub fn image_base64(mime_type: &str, data: &str) -> Self {
+        Self::image_url(format!("data:{};base64,{}", mime_type, data))
+    }
+}
+
+/// The image of an `image_url` part.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ImageUrl {
+    pub url: String,
+    /// Resolution hint: `low`, `high` or `auto`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn text_content_serializes_as_a_string() {
+        let value = serde_json::to_value(Message::user("hi")).unwrap();
+        assert_eq!(value, json!({ "role": "user", "content": "hi" }));
+    }
+
+    #[test]
+    fn parts_content_serializes_as_an_array() {
+        let message = Message::user_with_image("What is this?", "https://example.com/cat.png");
+        let value = serde_json::to_value(&message).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "role": "user",
+                "content": [
+                    { "type": "text", "text": "What is this?" },
+                    { "type": "image_url", "image_url": { "url": "https://example.com/cat.png" } }
+                ]
+            })
+        );
+        let parsed: Message = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed.content, message.content);
+        assert_eq!(parsed.text().as_deref(), Some("What is this?"));
+    }
+
+    #[test]
+    fn image_base64_builds_a_data_url() {
+        let part = ContentPart::image_base64("image/png", "iVBORw0KGgo=");
+        assert_eq!(
+            serde_json::to_value(part).unwrap(),
+            json!({ "type": "image_url", "image_url": { "url": "data:image/png;base64,iVBORw0KGgo=" } })
+        );
+    }
+}
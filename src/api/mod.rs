@@ -1,7 +1,17 @@
+pub mod anthropic;
+pub mod audio;
 pub mod client;
 pub mod config;
+pub mod conversation;
+pub mod embedding;
+pub mod image;
+pub mod interceptor;
 pub mod message;
+pub mod multipart;
 pub mod providers;
 pub mod request;
 pub mod response;
-pub mod session;
\ No newline at end of file
+pub mod session;
+pub mod sse;
+pub mod stream;
+pub mod tool;
\ No newline at end of file
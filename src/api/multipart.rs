@@ -0,0 +1,253 @@
+use crate::utils::error::{LlmHubError, Result};
+use bytes::Bytes;
+use futures::stream::{self, BoxStream, StreamExt};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio_util::io::ReaderStream;
+
+type SharedReader = Arc<Mutex<Option<Box<dyn AsyncRead + Send + Unpin>>>>;
+
+/// The audio payload of an upload, held in memory, streamed from disk or
+/// read from any [`AsyncRead`] source.
+#[derive(Clone)]
+pub enum AudioFile {
+    /// Audio bytes already in memory, sent under the given filename.
+    Bytes { data: Bytes, filename: String },
+    /// A file on disk, streamed into the request body without being read into memory.
+    Path(PathBuf),
+    /// An async reader of `len` bytes, streamed into the request body. The
+    /// reader can be uploaded once; clones share it.
+    Reader {
+        reader: SharedReader,
+        len: u64,
+        filename: String,
+    },
+}
+
+impl fmt::Debug for AudioFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bytes { data, filename } => f
+                .debug_struct("Bytes")
+                .field("len", &data.len())
+                .field("filename", filename)
+                .finish(),
+            Self::Path(path) => f.debug_tuple("Path").field(path).finish(),
+            Self::Reader { len, filename, .. } => f
+                .debug_struct("Reader")
+                .field("len", len)
+                .field("filename", filename)
+                .finish(),
+        }
+    }
+}
+
+impl AudioFile {
+    /// Creates an in-memory audio payload.
+    pub fn from_bytes(data: impl Into<Bytes>, filename: impl Into<String>) -> Self {
+        Self::Bytes {
+            data: data.into(),
+            filename: filename.into(),
+        }
+    }
+
+    /// Creates a path-based audio payload.
+    pub fn from_path(path: impl Into<PathBuf>) -> Self {
+        Self::Path(path.into())
+    }
+
+    /// Creates a payload streamed from `reader`, which must yield `len`
+    /// bytes; anything past `len` is not sent.
+    pub fn from_reader(
+        reader: impl AsyncRead + Send + Unpin + 'static,
+        len: u64,
+        filename: impl Into<String>,
+    ) -> Self {
+        Self::Reader {
+            reader: Arc::new(Mutex::new(Some(Box::new(reader)))),
+            len,
+            filename: filename.into(),
+        }
+    }
+
+    /// Returns the filename reported in the `Content-Disposition` header.
+    pub fn filename(&self) -> String {
+        match self {
+            Self::Bytes { filename, .. } | Self::Reader { filename, .. } => filename.clone(),
+            Self::Path(path) => path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| "audio".to_string()),
+        }
+    }
+
+    /// Returns the payload length in bytes, reading file metadata for path-based payloads.
+    pub async fn len(&self) -> Result<u64> {
+        match self {
+            Self::Bytes { data, .. } => Ok(data.len() as u64),
+            Self::Path(path) => Ok(tokio::fs::metadata(path).await?.len()),
+            Self::Reader { len, .. } => Ok(*len),
+        }
+    }
+
+    /// Opens the payload, returning its length and a stream of its bytes.
+    ///
+    /// A file's length is taken from the opened handle, so it matches the
+    /// bytes streamed even if the path is replaced in the meantime.
+    async fn open(self) -> Result<(u64, BoxStream<'static, std::io::Result<Bytes>>)> {
+        match self {
+            Self::Bytes { data, .. } => Ok((data.len() as u64, once(data))),
+            Self::Path(path) => {
+                let handle = tokio::fs::File::open(&path).await?;
+                let len = handle.metadata().await?.len();
+                Ok((len, ReaderStream::new(handle.take(len)).boxed()))
+            }
+            Self::Reader { reader, len, .. } => {
+                let reader = reader.lock().unwrap().take().ok_or_else(|| {
+                    LlmHubError::ConfigError("Audio reader has already been uploaded".to_string())
+                })?;
+                Ok((len, ReaderStream::new(reader.take(len)).boxed()))
+            }
+        }
+    }
+}
+
+enum Part {
+    Text { name: String, value: String },
+    File { name: String, file: AudioFile },
+}
+
+/// A `multipart/form-data` body whose total length is known up front.
+///
+/// File parts backed by a path are streamed from disk, so uploading large
+/// recordings keeps memory usage flat while still sending a `Content-Length`.
+pub struct MultipartForm {
+    boundary: String,
+    parts: Vec<Part>,
+}
+
+/// A fully assembled multipart body, ready to be attached to a request.
+pub struct MultipartBody {
+    pub content_type: String,
+    pub content_length: u64,
+    pub body: reqwest::Body,
+}
+
+impl MultipartForm {
+    /// Creates an empty form with a random boundary.
+    pub fn new() -> Self {
+        Self {
+            boundary: format!("llmhub-{}", uuid::Uuid::new_v4().simple()),
+            parts: Vec::new(),
+        }
+    }
+
+    /// Adds a text field.
+    pub fn text(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.parts.push(Part::Text {
+            name: name.into(),
+            value: value.into(),
+        });
+        self
+    }
+
+    /// Adds a file field.
+    pub fn file(mut self, name: impl Into<String>, file: AudioFile) -> Self {
+        self.parts.push(Part::File {
+            name: name.into(),
+            file,
+        });
+        self
+    }
+
+    /// Assembles the form into a streaming body with a precomputed length.
+    pub async fn into_body(self) -> Result<MultipartBody> {
+        let boundary = self.boundary;
+        let mut content_length = 0u64;
+        let mut segments = Vec::new();
+
+        for part in self.parts {
+            match part {
+                Part::Text { name, value } => {
+                    let chunk = Bytes::from(format!(
+                        "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                        boundary,
+                        escape_quoted(&name),
+                        value
+                    ));
+                    content_length += chunk.len() as u64;
+                    segments.push(once(chunk));
+                }
+                Part::File { name, file } => {
+                    let header = Bytes::from(format!(
+                        "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: application/octet-stream\r\n\r\n",
+                        boundary,
+                        escape_quoted(&name),
+                        escape_quoted(&file.filename())
+                    ));
+                    let (len, data) = file.open().await?;
+                    content_length += header.len() as u64 + len + 2;
+                    segments.push(once(header));
+                    segments.push(data);
+                    segments.push(once(Bytes::from_static(b"\r\n")));
+                }
+            }
+        }
+
+        let closing = Bytes::from(format!("--{}--\r\n", boundary));
+        content_length += closing.len() as u64;
+        segments.push(once(closing));
+
+        let body = stream::iter(segments).flatten();
+        Ok(MultipartBody {
+            content_type: format!("multipart/form-data; boundary={}", boundary),
+            content_length,
+            body: reqwest::Body::wrap_stream(body),
+        })
+    }
+}
+
+impl Default for MultipartForm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Percent-encodes the characters that would break out of a quoted
+/// `Content-Disposition` parameter, as browsers do for form uploads.
+fn escape_quoted(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn once(chunk: Bytes) -> BoxStream<'static, std::io::Result<Bytes>> {
+    stream::once(async move { Ok(chunk) }).boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_quotes_and_line_breaks_in_filenames() {
+        assert_eq!(escape_quoted("a\"b\r\nc.mp3"), "a%22b%0D%0Ac.mp3");
+    }
+
+    #[tokio::test]
+    async fn reader_payload_streams_once_and_stops_at_len() {
+        let file = AudioFile::from_reader(&b"abcdef"[..], 4, "clip.wav");
+        let clone = file.clone();
+        let (len, data) = file.open().await.unwrap();
+        let bytes: Vec<Bytes> = data.map(|chunk| chunk.unwrap()).collect().await;
+        assert_eq!(len, 4);
+        assert_eq!(bytes.concat(), b"abcd");
+        assert!(matches!(
+            clone.open().await,
+            Err(LlmHubError::ConfigError(_))
+        ));
+    }
+}
@@ -1,10 +1,28 @@
+use crate::api::anthropic::ANTHROPIC_VERSION;
+use crate::api::request::ResponseType;
+use crate::models::models::ModelFamily;
 use crate::utils::error::{LlmHubError, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::hash::Hash;
 use strum_macros::{Display, EnumString};
 
-#[derive(Debug, Serialize, Deserialize, Copy, PartialEq, Eq, Clone, Hash, Display, EnumString)]
+/// Supported API providers.
+///
+/// The serialized and `Display` representations are the canonical
+/// lowercase name (`"siliconflow"`, `"deepseek"`, `"qianfan"`,
+/// `"anthropic"`, `"openai"`, `"zhipuai"`, `"alibailian"`, `"xai"`,
+/// `"volcengine"`, `"tencent"`, `"google"`), so every spelling round-trips
+/// through [`FromStr`](std::str::FromStr) and serde. Parsing and
+/// deserialization are more forgiving: case, `_`, `-` and spaces are
+/// ignored, and a few common aliases are accepted (`"open_ai"`, `"claude"`,
+/// `"dashscope"`, `"gemini"`, ...), so hand-edited config files and files
+/// written with the older variant-name spelling (`"OpenAI"`) still load.
+/// Environment variable prefixes are the names upper-cased (see
+/// [`ApiProvider::env_prefix`]).
+#[derive(Debug, Serialize, Copy, PartialEq, Eq, Clone, Hash, Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
 pub enum ApiProvider {
     Siliconflow,
     Deepseek,
@@ -19,6 +37,49 @@ pub enum ApiProvider {
     GOOGLE,
 }
 
+impl std::str::FromStr for ApiProvider {
+    type Err = LlmHubError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let normalized: String = s
+            .chars()
+            .filter(|c| !matches!(c, '_' | '-' | ' ' | '.'))
+            .flat_map(char::to_lowercase)
+            .collect();
+        let provider = match normalized.as_str() {
+            "siliconflow" | "siliconcloud" => ApiProvider::Siliconflow,
+            "deepseek" => ApiProvider::Deepseek,
+            "qianfan" | "baidu" => ApiProvider::Qianfan,
+            "anthropic" | "claude" => ApiProvider::Anthropic,
+            "openai" => ApiProvider::OpenAI,
+            "zhipuai" | "zhipu" | "bigmodel" => ApiProvider::ZhipuAI,
+            "alibailian" | "bailian" | "alibaba" | "aliyun" | "dashscope" => {
+                ApiProvider::ALIBAILIAN
+            }
+            "xai" | "grok" => ApiProvider::XAI,
+            "volcengine" | "volcano" | "ark" | "doubao" => ApiProvider::Volcengine,
+            "tencent" | "hunyuan" => ApiProvider::Tencent,
+            "google" | "gemini" => ApiProvider::GOOGLE,
+            _ => {
+                return Err(LlmHubError::ProviderError(format!(
+                    "Unknown provider `{}`",
+                    s
+                )));
+            }
+        };
+        Ok(provider)
+    }
+}
+
+impl<'de> Deserialize<'de> for ApiProvider {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString)]
 #[strum(serialize_all = "snake_case")]
 pub enum ApiType {
@@ -33,6 +94,15 @@ pub enum ApiType {
 }
 
 impl ApiType {
+    /// Returns the `Accept` header for a non-streaming request to this endpoint.
+    /// Streaming requests always use `text/event-stream`.
+    pub fn accept(&self) -> &'static str {
+        match self {
+            Self::AudioSpeech => "audio/*",
+            _ => "application/json",
+        }
+    }
+
     pub fn default_path(&self) -> &'static str {
         match self {
             Self::Chat => "/chat/completions",
@@ -40,13 +110,24 @@ impl ApiType {
             Self::ImageEdit => "/images/edits",
             Self::Embedding => "/embeddings",
             Self::AudioSpeech => "/audio/speech",
-            Self::AudioTranscription => "/audio/transcribes",
+            Self::AudioTranscription => "/audio/transcriptions",
             Self::AudioTranslation => "/audio/translations",
             Self::ListModels => "/models",
         }
     }
 }
 
+/// How a provider expects the API key to be sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthScheme {
+    /// `Authorization: Bearer <key>`.
+    Bearer,
+    /// The raw key in the named header, e.g. `x-api-key`.
+    Header(&'static str),
+    /// The raw key in the named query parameter, e.g. `key`.
+    QueryParam(&'static str),
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct EndpointConfig {
     pub base_url: String,
@@ -72,6 +153,21 @@ impl EndpointConfig {
 }
 
 impl ApiProvider {
+    /// Every provider, in declaration order.
+    pub const ALL: [ApiProvider; 11] = [
+        ApiProvider::Siliconflow,
+        ApiProvider::Deepseek,
+        ApiProvider::Qianfan,
+        ApiProvider::Anthropic,
+        ApiProvider::OpenAI,
+        ApiProvider::ZhipuAI,
+        ApiProvider::ALIBAILIAN,
+        ApiProvider::XAI,
+        ApiProvider::Volcengine,
+        ApiProvider::Tencent,
+        ApiProvider::GOOGLE,
+    ];
+
     pub fn base_url(&self) -> &str {
         match self {
             ApiProvider::Siliconflow => "https://api.siliconflow.cn/v1/",
@@ -88,6 +184,116 @@ impl ApiProvider {
         }
     }
 
+    /// Guesses the provider serving a bare model id, e.g. one read from logs.
+    ///
+    /// Uses the vendor's naming conventions (`gpt-`/`o1` for OpenAI,
+    /// `claude-` for Anthropic, `glm-` for ZhipuAI, ...), so a model offered
+    /// by several providers maps to its first-party API. Ids namespaced with
+    /// an organization (`deepseek-ai/DeepSeek-R1`) are Siliconflow's format.
+    pub fn from_model_id(id: &str) -> Option<ApiProvider> {
+        let id = id.trim().to_ascii_lowercase();
+        if id.contains('/') {
+            return Some(ApiProvider::Siliconflow);
+        }
+        let starts_with_any = |prefixes: &[&str]| prefixes.iter().any(|p| id.starts_with(p));
+        let provider =
+            if starts_with_any(&["gpt-", "chatgpt-", "o1", "o3", "o4", "text-embedding-"]) {
+                ApiProvider::OpenAI
+            } else if id.starts_with("claude-") {
+                ApiProvider::Anthropic
+            } else if id.starts_with("deepseek-") {
+                ApiProvider::Deepseek
+            } else if starts_with_any(&["glm-", "cogview", "cogvideo", "codegeex", "embedding-"]) {
+                ApiProvider::ZhipuAI
+            } else if starts_with_any(&["qwen", "qwq"]) {
+                ApiProvider::ALIBAILIAN
+            } else if id.starts_with("grok-") {
+                ApiProvider::XAI
+            } else if starts_with_any(&["gemini-", "gemma-"]) {
+                ApiProvider::GOOGLE
+            } else if id.starts_with("doubao-") {
+                ApiProvider::Volcengine
+            } else if id.starts_with("hunyuan-") {
+                ApiProvider::Tencent
+            } else if id.starts_with("ernie-") {
+                ApiProvider::Qianfan
+            } else {
+                return None;
+            };
+        Some(provider)
+    }
+
+    /// Returns the prefix used for this provider's environment variables,
+    /// e.g. `OPENAI` for `OPENAI_API_KEY` and `OPENAI_API_BASE`.
+    pub fn env_prefix(&self) -> &'static str {
+        match self {
+            ApiProvider::OpenAI => "OPENAI",
+            ApiProvider::Anthropic => "ANTHROPIC",
+            ApiProvider::Deepseek => "DEEPSEEK",
+            ApiProvider::Siliconflow => "SILICONFLOW",
+            ApiProvider::Qianfan => "QIANFAN",
+            ApiProvider::ZhipuAI => "ZHIPUAI",
+            ApiProvider::Volcengine => "VOLCENGINE",
+            ApiProvider::XAI => "XAI",
+            ApiProvider::Tencent => "TENCENT",
+            ApiProvider::ALIBAILIAN => "ALIBAILIAN",
+            ApiProvider::GOOGLE => "GOOGLE",
+        }
+    }
+
+    /// Returns headers every request to this provider must carry, such as
+    /// Anthropic's `anthropic-version`. Headers configured with
+    /// [`Client::with_provider_headers`](crate::api::client::Client::with_provider_headers)
+    /// take precedence.
+    pub fn default_headers(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            ApiProvider::Anthropic => &[("anthropic-version", ANTHROPIC_VERSION)],
+            _ => &[],
+        }
+    }
+
+    /// Returns how requests to this provider are authenticated.
+    pub fn auth_scheme(&self) -> AuthScheme {
+        match self {
+            ApiProvider::Anthropic => AuthScheme::Header("x-api-key"),
+            _ => AuthScheme::Bearer,
+        }
+    }
+
+    /// Returns whether this provider accepts `response_format` with the given type.
+    pub fn supports_response_format(&self, response_type: &ResponseType) -> bool {
+        match response_type {
+            ResponseType::Text => true,
+            ResponseType::JsonObject => {
+                !matches!(self, ApiProvider::Anthropic | ApiProvider::Tencent)
+            }
+            ResponseType::JsonSchema => matches!(
+                self,
+                ApiProvider::OpenAI | ApiProvider::XAI | ApiProvider::GOOGLE
+            ),
+        }
+    }
+
+    /// Returns whether this provider plausibly serves models of `family`.
+    pub fn hosts_family(&self, family: ModelFamily) -> bool {
+        let families: &[ModelFamily] = match self {
+            ApiProvider::OpenAI => &[ModelFamily::GPT],
+            ApiProvider::Anthropic => &[ModelFamily::Claude],
+            ApiProvider::Deepseek => &[ModelFamily::Deepseek],
+            ApiProvider::Siliconflow => {
+                &[ModelFamily::Deepseek, ModelFamily::Qwen, ModelFamily::GLM]
+            }
+            ApiProvider::Qianfan => &[ModelFamily::Deepseek],
+            ApiProvider::ZhipuAI => &[ModelFamily::GLM],
+            ApiProvider::ALIBAILIAN => &[ModelFamily::Qwen, ModelFamily::Deepseek],
+            ApiProvider::XAI => &[ModelFamily::Grok],
+            ApiProvider::Volcengine => &[ModelFamily::Doubao, ModelFamily::Deepseek],
+            ApiProvider::Tencent => &[ModelFamily::Deepseek],
+            ApiProvider::GOOGLE => &[ModelFamily::Gemini],
+        };
+        families.contains(&family)
+    }
+
     pub fn get_endpoint_config(&self) -> EndpointConfig {
         let base_url = self.base_url().trim_end_matches('/').to_string();
         let (supported_types, custom_paths) = match self {
@@ -115,9 +321,17 @@ impl ApiProvider {
                     ApiType::Embedding,
                     ApiType::AudioSpeech,
                     ApiType::AudioTranscription,
+                    ApiType::ListModels,
                 ],
                 HashMap::new(),
             ),
+            ApiProvider::Deepseek | ApiProvider::XAI | ApiProvider::GOOGLE => {
+                (vec![ApiType::Chat, ApiType::ListModels], HashMap::new())
+            }
+            ApiProvider::ZhipuAI => (
+                vec![ApiType::Chat, ApiType::Embedding, ApiType::ImageGeneration],
+                HashMap::new(),
+            ),
             _ => (vec![ApiType::Chat], HashMap::new()),
         };
         EndpointConfig {
@@ -127,3 +341,88 @@ impl ApiProvider {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_round_trip_through_from_str_and_serde() {
+        for provider in ApiProvider::ALL {
+            let name = provider.to_string();
+            assert_eq!(name, name.to_lowercase());
+            assert_eq!(name.parse::<ApiProvider>().unwrap(), provider);
+
+            let json = serde_json::to_string(&provider).unwrap();
+            assert_eq!(json, format!("\"{}\"", name));
+            assert_eq!(
+                serde_json::from_str::<ApiProvider>(&json).unwrap(),
+                provider
+            );
+        }
+    }
+
+    #[test]
+    fn accepts_common_spellings() {
+        let spellings = [
+            ("openai", ApiProvider::OpenAI),
+            ("OpenAI", ApiProvider::OpenAI),
+            ("open_ai", ApiProvider::OpenAI),
+            ("Open-AI", ApiProvider::OpenAI),
+            ("anthropic", ApiProvider::Anthropic),
+            ("Claude", ApiProvider::Anthropic),
+            ("deepseek", ApiProvider::Deepseek),
+            ("DeepSeek", ApiProvider::Deepseek),
+            ("zhipu_ai", ApiProvider::ZhipuAI),
+            ("BigModel", ApiProvider::ZhipuAI),
+            ("ALIBAILIAN", ApiProvider::ALIBAILIAN),
+            ("dashscope", ApiProvider::ALIBAILIAN),
+            ("x.ai", ApiProvider::XAI),
+            ("Grok", ApiProvider::XAI),
+            ("GOOGLE", ApiProvider::GOOGLE),
+            ("gemini", ApiProvider::GOOGLE),
+            ("Silicon Flow", ApiProvider::Siliconflow),
+            ("siliconcloud", ApiProvider::Siliconflow),
+            ("Qianfan", ApiProvider::Qianfan),
+            ("baidu", ApiProvider::Qianfan),
+            ("Volc-Engine", ApiProvider::Volcengine),
+            ("doubao", ApiProvider::Volcengine),
+            ("TENCENT", ApiProvider::Tencent),
+            ("hunyuan", ApiProvider::Tencent),
+        ];
+        for (spelling, provider) in spellings {
+            assert_eq!(spelling.parse::<ApiProvider>().unwrap(), provider);
+            let json = format!("\"{}\"", spelling);
+            assert_eq!(
+                serde_json::from_str::<ApiProvider>(&json).unwrap(),
+                provider
+            );
+        }
+        assert!("openaii".parse::<ApiProvider>().is_err());
+    }
+
+    #[test]
+    fn guesses_the_provider_of_each_model_family() {
+        let ids = [
+            ("gpt-4o-mini", Some(ApiProvider::OpenAI)),
+            ("o1-preview", Some(ApiProvider::OpenAI)),
+            ("text-embedding-3-small", Some(ApiProvider::OpenAI)),
+            ("claude-3-7-sonnet-20250219", Some(ApiProvider::Anthropic)),
+            ("deepseek-reasoner", Some(ApiProvider::Deepseek)),
+            ("glm-4-flash", Some(ApiProvider::ZhipuAI)),
+            ("embedding-3", Some(ApiProvider::ZhipuAI)),
+            ("qwen-max", Some(ApiProvider::ALIBAILIAN)),
+            ("QwQ-32B", Some(ApiProvider::ALIBAILIAN)),
+            ("grok-3", Some(ApiProvider::XAI)),
+            ("gemini-2.5-flash", Some(ApiProvider::GOOGLE)),
+            ("deepseek-ai/DeepSeek-R1", Some(ApiProvider::Siliconflow)),
+            ("doubao-pro-32k", Some(ApiProvider::Volcengine)),
+            ("hunyuan-turbo", Some(ApiProvider::Tencent)),
+            ("ernie-4.0-8k", Some(ApiProvider::Qianfan)),
+            ("llama-3-70b", None),
+        ];
+        for (id, provider) in ids {
+            assert_eq!(ApiProvider::from_model_id(id), provider, "{}", id);
+        }
+    }
+}
@@ -1,7 +1,23 @@
-use crate::api::message::Message;
+use crate::api::message::{Message, Role};
+use crate::api::providers::{ApiProvider, ApiType};
 use crate::api::session::Session;
+use crate::api::tool::Tool;
 use crate::models::models::Model;
-use serde::Serialize;
+use crate::models::pricing::Cost;
+use crate::utils::error::{LlmHubError, Result};
+use crate::utils::tokens::Tokenizer;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Maximum number of `metadata` entries OpenAI accepts.
+pub const MAX_METADATA_ENTRIES: usize = 16;
+/// Maximum length of a `metadata` key, in characters.
+pub const MAX_METADATA_KEY_CHARS: usize = 64;
+/// Maximum length of a `metadata` value, in characters.
+pub const MAX_METADATA_VALUE_CHARS: usize = 512;
+/// Maximum number of `stop` sequences OpenAI accepts.
+pub const MAX_STOP_SEQUENCES: usize = 4;
 
 // Your original ResponseType and ResponseFormat are kept.
 #[derive(Debug, Serialize, Clone)]
@@ -9,21 +25,127 @@ use serde::Serialize;
 pub enum ResponseType {
     Text,
     JsonObject,
+    JsonSchema,
 }
 
+#[serde_with::skip_serializing_none]
 #[derive(Debug, Serialize, Clone)]
 pub struct ResponseFormat {
     #[serde(rename = "type")]
     pub response_type: ResponseType,
+    /// The schema, for [`ResponseType::JsonSchema`].
+    pub json_schema: Option<JsonSchemaFormat>,
+}
+
+impl ResponseFormat {
+    pub fn text() -> Self {
+        Self {
+            response_type: ResponseType::Text,
+            json_schema: None,
+        }
+    }
+
+    pub fn json_object() -> Self {
+        Self {
+            response_type: ResponseType::JsonObject,
+            json_schema: None,
+        }
+    }
+
+    /// Asks for output conforming to `schema`, with strict adherence.
+    ///
+    /// Unlike other formats, a schema is never silently dropped: `Client`
+    /// rejects it with a `ProviderError` for providers without structured
+    /// outputs.
+    pub fn json_schema(name: impl Into<String>, schema: serde_json::Value) -> Self {
+        Self {
+            response_type: ResponseType::JsonSchema,
+            json_schema: Some(JsonSchemaFormat {
+                name: name.into(),
+                description: None,
+                strict: Some(true),
+                schema,
+            }),
+        }
+    }
+}
+
+/// The `json_schema` object of a structured-output response format.
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Serialize, Clone)]
+pub struct JsonSchemaFormat {
+    pub name: String,
+    pub description: Option<String>,
+    pub strict: Option<bool>,
+    pub schema: serde_json::Value,
+}
+
+/// How much reasoning ("thinking") the model should do before answering.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    Low,
+    Medium,
+    High,
+}
+
+impl ReasoningEffort {
+    /// Returns the OpenAI-style `reasoning_effort` value.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+
+    /// Returns the thinking token budget used for providers that take one.
+    pub fn budget_tokens(&self) -> u32 {
+        match self {
+            Self::Low => 1024,
+            Self::Medium => 4096,
+            Self::High => 16384,
+        }
+    }
+}
+
+/// Parses `low`, `medium` or `high`, ignoring case and surrounding
+/// whitespace.
+impl FromStr for ReasoningEffort {
+    type Err = LlmHubError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "low" => Ok(Self::Low),
+            "medium" => Ok(Self::Medium),
+            "high" => Ok(Self::High),
+            _ => Err(LlmHubError::ConfigError(format!(
+                "Unknown reasoning effort `{}`",
+                s
+            ))),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ReasoningEffort {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
 }
 
 // Your comprehensive RequestOptions is kept entirely.
-#[derive(Debug, Serialize, Clone, Default)]
 #[serde_with::skip_serializing_none]
+#[derive(Debug, Serialize, Clone, Default)]
 pub struct RequestOptions {
+    /// Whether OpenAI should store the completion for later retrieval.
     pub store: Option<bool>,
-    pub reasoning_effort: Option<String>,
-    pub metadata: Option<serde_json::Value>,
+    pub reasoning_effort: Option<ReasoningEffort>,
+    pub thinking: Option<serde_json::Value>,
+    /// Tags for stored completions; see [`ApiRequest::validate`] for the limits.
+    pub metadata: Option<HashMap<String, String>>,
     pub frequency_penalty: Option<f32>,
     pub logit_bias: Option<serde_json::Value>,
     pub logprobs: Option<bool>,
@@ -38,7 +160,10 @@ pub struct RequestOptions {
     pub response_format: Option<ResponseFormat>,
     pub seed: Option<u32>,
     pub service_tier: Option<String>,
-    pub stop: Option<String>,
+    /// Up to [`MAX_STOP_SEQUENCES`] sequences; a single one is sent as a
+    /// plain string, which every provider accepts.
+    #[serde(serialize_with = "serialize_stop")]
+    pub stop: Option<Vec<String>>,
     pub stream: Option<bool>,
     pub stream_options: Option<serde_json::Value>,
     pub temperature: Option<f32>,
@@ -47,15 +172,261 @@ pub struct RequestOptions {
     pub tools: Option<serde_json::Value>,
     pub tool_choice: Option<serde_json::Value>,
     pub user: Option<String>,
+    /// Constrains output to a JSON schema (vLLM-style guided decoding). The
+    /// guided fields are only sent to providers enabled with
+    /// [`Client::with_guided_decoding`](crate::api::client::Client::with_guided_decoding).
+    pub guided_json: Option<serde_json::Value>,
+    /// Constrains output to match a regular expression.
+    pub guided_regex: Option<String>,
+    /// Constrains output to a context-free grammar, e.g. GBNF.
+    pub guided_grammar: Option<String>,
+}
+
+impl RequestOptions {
+    /// Returns a [`RequestOptionsBuilder`] that validates value ranges.
+    pub fn builder() -> RequestOptionsBuilder {
+        RequestOptionsBuilder::default()
+    }
+
+    /// Sets `reasoning_effort`, translated per provider like
+    /// [`ApiRequest::with_reasoning`].
+    pub fn reasoning_effort(mut self, effort: ReasoningEffort) -> Self {
+        self.reasoning_effort = Some(effort);
+        self
+    }
+
+    /// Returns a copy of these options that `model` and its provider accept.
+    ///
+    /// Unsupported options are dropped and reasoning models get their token
+    /// cap in the field they expect; each adjustment is logged at debug
+    /// level. `reasoning_effort` is kept for OpenAI, xAI and Google, left for
+    /// the Anthropic adapter to turn into a `thinking` budget on models that
+    /// support one, and dropped elsewhere. With thinking on, Anthropic rejects
+    /// sampling options, so those are dropped too. The client applies this to
+    /// every chat request before sending.
+    pub fn normalize_for(&self, model: &Model) -> RequestOptions {
+        let provider = model.provider();
+        let mut options = self.clone();
+
+        if let Some(format) = &options.response_format
+            && !provider.supports_response_format(&format.response_type)
+        {
+            log::debug!(
+                "Dropping response_format {:?} unsupported by {}",
+                format.response_type,
+                provider
+            );
+            options.response_format = None;
+        }
+        if model.is_reasoning() {
+            let temperature = options.temperature.take();
+            let top_p = options.top_p.take();
+            if temperature.is_some() || top_p.is_some() {
+                log::debug!(
+                    "Dropping sampling options unsupported by reasoning model {}",
+                    model
+                );
+            }
+            // OpenAI's reasoning models reject `max_tokens` in favour of
+            // `max_completion_tokens`.
+            if provider == ApiProvider::OpenAI
+                && let Some(max_tokens) = options.max_tokens.take()
+            {
+                log::debug!("Sending max_tokens as max_completion_tokens for {}", model);
+                options.max_completion_tokens.get_or_insert(max_tokens);
+            }
+        }
+        if provider == ApiProvider::OpenAI && options.top_k.take().is_some() {
+            log::debug!("Dropping top_k unsupported by {}", provider);
+        }
+        if !model.supports_thinking() && options.thinking.take().is_some() {
+            log::debug!("Dropping thinking unsupported by {}", model);
+        }
+        let takes_effort = match provider {
+            ApiProvider::OpenAI | ApiProvider::XAI | ApiProvider::GOOGLE => true,
+            ApiProvider::Anthropic => model.supports_thinking(),
+            _ => false,
+        };
+        if !takes_effort && let Some(effort) = options.reasoning_effort.take() {
+            log::debug!("Ignoring reasoning effort {:?} for {}", effort, model);
+        }
+        if provider == ApiProvider::Anthropic {
+            let thinking = match &options.thinking {
+                Some(thinking) => thinking["type"] != "disabled",
+                None => options.reasoning_effort.is_some(),
+            };
+            if thinking {
+                let temperature = options.temperature.take();
+                let top_p = options.top_p.take();
+                let top_k = options.top_k.take();
+                if temperature.is_some() || top_p.is_some() || top_k.is_some() {
+                    log::debug!(
+                        "Dropping sampling options unsupported with thinking on {}",
+                        model
+                    );
+                }
+            }
+        }
+        options
+    }
+
+    /// Returns whether any guided-decoding constraint is set.
+    pub fn has_guided_decoding(&self) -> bool {
+        self.guided_json.is_some() || self.guided_regex.is_some() || self.guided_grammar.is_some()
+    }
+}
+
+/// Builds [`RequestOptions`] fluently, checking sampling parameters against
+/// the ranges the OpenAI API accepts when [`build`](Self::build) is called.
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptionsBuilder {
+    options: RequestOptions,
+}
+
+impl RequestOptionsBuilder {
+    /// Sampling temperature, from 0.0 to 2.0.
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.options.temperature = Some(temperature);
+        self
+    }
+
+    /// Nucleus sampling probability mass, from 0.0 to 1.0.
+    pub fn top_p(mut self, top_p: f32) -> Self {
+        self.options.top_p = Some(top_p);
+        self
+    }
+
+    pub fn top_k(mut self, top_k: u32) -> Self {
+        self.options.top_k = Some(top_k);
+        self
+    }
+
+    pub fn max_tokens(mut self, max_tokens: u32) -> Self {
+        self.options.max_tokens = Some(max_tokens);
+        self
+    }
+
+    pub fn max_completion_tokens(mut self, max_completion_tokens: u32) -> Self {
+        self.options.max_completion_tokens = Some(max_completion_tokens);
+        self
+    }
+
+    /// Presence penalty, from -2.0 to 2.0.
+    pub fn presence_penalty(mut self, penalty: f32) -> Self {
+        self.options.presence_penalty = Some(penalty);
+        self
+    }
+
+    /// Frequency penalty, from -2.0 to 2.0.
+    pub fn frequency_penalty(mut self, penalty: f32) -> Self {
+        self.options.frequency_penalty = Some(penalty);
+        self
+    }
+
+    /// Adds a stop sequence.
+    pub fn stop(mut self, stop: impl Into<String>) -> Self {
+        self.options
+            .stop
+            .get_or_insert_with(Vec::new)
+            .push(stop.into());
+        self
+    }
+
+    /// Replaces the stop sequences.
+    pub fn stop_sequences(mut self, stop: Vec<String>) -> Self {
+        self.options.stop = Some(stop);
+        self
+    }
+
+    pub fn n(mut self, n: u32) -> Self {
+        self.options.n = Some(n);
+        self
+    }
+
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.options.seed = Some(seed);
+        self
+    }
+
+    pub fn user(mut self, user: impl Into<String>) -> Self {
+        self.options.user = Some(user.into());
+        self
+    }
+
+    pub fn stream(mut self, stream: bool) -> Self {
+        self.options.stream = Some(stream);
+        self
+    }
+
+    pub fn response_format(mut self, format: ResponseFormat) -> Self {
+        self.options.response_format = Some(format);
+        self
+    }
+
+    pub fn reasoning_effort(mut self, effort: ReasoningEffort) -> Self {
+        self.options.reasoning_effort = Some(effort);
+        self
+    }
+
+    /// Validates the options, returning a `ConfigError` naming the first
+    /// value out of range.
+    pub fn build(self) -> Result<RequestOptions> {
+        let options = self.options;
+        if let Some(stop) = &options.stop
+            && stop.len() > MAX_STOP_SEQUENCES
+        {
+            return Err(LlmHubError::ConfigError(format!(
+                "stop has {} sequences; at most {} are allowed",
+                stop.len(),
+                MAX_STOP_SEQUENCES
+            )));
+        }
+        check_range("temperature", options.temperature, 0.0, 2.0)?;
+        check_range("top_p", options.top_p, 0.0, 1.0)?;
+        check_range("presence_penalty", options.presence_penalty, -2.0, 2.0)?;
+        check_range("frequency_penalty", options.frequency_penalty, -2.0, 2.0)?;
+        Ok(options)
+    }
+}
+
+fn serialize_stop<S: serde::Serializer>(
+    stop: &Option<Vec<String>>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    match stop.as_deref() {
+        Some([single]) => serializer.serialize_str(single),
+        stop => stop.serialize(serializer),
+    }
+}
+
+fn check_range(name: &str, value: Option<f32>, min: f32, max: f32) -> Result<()> {
+    match value {
+        Some(value) if !(min..=max).contains(&value) => Err(LlmHubError::ConfigError(format!(
+            "{} must be between {} and {}, got {}",
+            name, min, max, value
+        ))),
+        _ => Ok(()),
+    }
 }
 
 /// Represents the complete, serializable request body sent to the API.
+///
+/// The wire shape is the OpenAI chat-completions body: `model` is the
+/// model's canonical id string, `messages` follow, and every set field of
+/// [`RequestOptions`] is flattened to the top level while unset ones are
+/// omitted rather than sent as `null`. For example, a streaming request with
+/// only a temperature serializes as
+/// `{"model":"deepseek-chat","messages":[...],"stream":true,"temperature":0.7}`.
 #[derive(Debug, Serialize, Clone)]
 pub struct ApiRequest {
     pub model: Model,
     pub messages: Vec<Message>,
     #[serde(flatten)]
     pub options: RequestOptions,
+    /// Extra HTTP headers sent with this request only, e.g. a trace id.
+    /// Headers that would replace the API key are ignored.
+    #[serde(skip)]
+    pub headers: HashMap<String, String>,
 }
 
 impl ApiRequest {
@@ -66,6 +437,7 @@ impl ApiRequest {
             model,
             messages,
             options: RequestOptions::default(),
+            headers: HashMap::new(),
         }
     }
 
@@ -75,15 +447,394 @@ impl ApiRequest {
         self
     }
     
+    /// Replaces the conversation messages.
+    pub fn with_messages(mut self, messages: Vec<Message>) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    /// Adds an HTTP header sent with this request only.
+    pub fn with_header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(key.into(), value.into());
+        self
+    }
+
     /// A convenient way to set the streaming option.
     pub fn stream(mut self, stream: bool) -> Self {
         self.options.stream = Some(stream);
         self
     }
 
+    /// Requests deeper reasoning. The effort is stored as is and sent in the
+    /// form the provider expects at send time: `reasoning_effort` for
+    /// OpenAI-compatible reasoning APIs, a `thinking` budget for Anthropic.
+    /// Providers without a reasoning switch drop it (see
+    /// [`RequestOptions::normalize_for`]).
+    pub fn with_reasoning(mut self, effort: ReasoningEffort) -> Self {
+        self.options.reasoning_effort = Some(effort);
+        self
+    }
+
+    /// Sets the tools the model may call.
+    pub fn with_tools(mut self, tools: Vec<Tool>) -> Self {
+        self.options.tools = serde_json::to_value(tools).ok();
+        self
+    }
+
     /// Adds a message to the request.
     pub fn add_message(mut self, message: Message) -> Self {
         self.messages.push(message);
         self
     }
+
+    /// Separates system messages from the rest of the conversation, for
+    /// providers such as Anthropic that take the system prompt as a top-level
+    /// field. Multiple system messages are joined with blank lines.
+    pub fn split_system(&self) -> (Option<String>, Vec<Message>) {
+        let (system, messages): (Vec<&Message>, Vec<&Message>) =
+            self.messages.iter().partition(|m| m.role == Role::System);
+        let system: Vec<String> = system
+            .iter()
+            .filter_map(|m| m.text().map(|text| text.into_owned()))
+            .collect();
+        let system = (!system.is_empty()).then(|| system.join("\n\n"));
+        (system, messages.into_iter().cloned().collect())
+    }
+
+    /// Checks that the request can be sent to the chat endpoint, rejecting
+    /// embedding, image and video models with a `ProviderError` and
+    /// `metadata` beyond OpenAI's limits (at most 16 entries, keys up to 64
+    /// and values up to 512 characters) with a `ConfigError`.
+    ///
+    /// `Client` runs this before every chat request.
+    pub fn validate(&self) -> Result<()> {
+        if !self.model.is_chat_capable() {
+            return Err(LlmHubError::ProviderError(format!(
+                "Model '{}' is {}-only and can't be used for chat",
+                self.model,
+                self.model.kind()
+            )));
+        }
+        if let Some(metadata) = &self.options.metadata {
+            if metadata.len() > MAX_METADATA_ENTRIES {
+                return Err(LlmHubError::ConfigError(format!(
+                    "metadata has {} entries; at most {} are allowed",
+                    metadata.len(),
+                    MAX_METADATA_ENTRIES
+                )));
+            }
+            for (key, value) in metadata {
+                if key.chars().count() > MAX_METADATA_KEY_CHARS {
+                    return Err(LlmHubError::ConfigError(format!(
+                        "metadata key '{}' exceeds {} characters",
+                        key, MAX_METADATA_KEY_CHARS
+                    )));
+                }
+                if value.chars().count() > MAX_METADATA_VALUE_CHARS {
+                    return Err(LlmHubError::ConfigError(format!(
+                        "metadata value for '{}' exceeds {} characters",
+                        key, MAX_METADATA_VALUE_CHARS
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the model's provider exposes a chat endpoint and plausibly
+    /// serves the model's family, catching mis-wired requests before sending.
+    ///
+    /// This is opt-in: `Client` does not call it, so unusual routings still work.
+    pub fn check_compatibility(&self) -> Result<()> {
+        let provider = self.model.provider();
+        provider.get_endpoint_config().get_url(ApiType::Chat)?;
+        let family = self.model.family();
+        if !provider.hosts_family(family) {
+            return Err(LlmHubError::ProviderError(format!(
+                "Model '{}' ({} family) is not served by provider {}",
+                self.model, family, provider
+            )));
+        }
+        Ok(())
+    }
+
+    /// Estimates an upper bound on the cost of this request: the prompt's
+    /// tokens at the input price plus `max_completion_tokens`/`max_tokens`
+    /// (or the model's maximum output) at the output price.
+    ///
+    /// Returns `None` when the model's pricing or output cap is unknown.
+    pub fn estimate_max_cost(&self, tokenizer: &dyn Tokenizer) -> Option<Cost> {
+        let pricing = self.model.pricing()?;
+        let input_tokens = tokenizer.count_messages(&self.messages);
+        let output_tokens = self
+            .options
+            .max_completion_tokens
+            .or(self.options.max_tokens)
+            .or_else(|| self.model.max_output_tokens())?;
+        Some(pricing.cost(
+            u32::try_from(input_tokens).unwrap_or(u32::MAX),
+            output_tokens,
+        ))
+    }
+
+    /// Returns a stable key identifying this request, suitable for response
+    /// caching and idempotency keys.
+    ///
+    /// The request is serialized with object keys sorted recursively, so
+    /// logically equal requests produce the same key regardless of the order
+    /// in which map-valued options such as `metadata` or `tools` were built.
+    /// A request that fails to serialize is a `SerializationError` rather
+    /// than a key shared with every other such request.
+    pub fn cache_key(&self) -> Result<String> {
+        let value = serde_json::to_value(self)?;
+        let mut canonical = String::new();
+        write_canonical(&value, &mut canonical);
+        Ok(format!("{:016x}", fnv1a_64(canonical.as_bytes())))
+    }
+}
+
+/// Writes `value` as compact JSON with object keys in sorted order.
+fn write_canonical(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&map[key], out);
+            }
+            out.push('}');
+        }
+        serde_json::Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+/// 64-bit FNV-1a, used because its output is stable across Rust releases,
+/// unlike `std`'s `DefaultHasher`.
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::models::{CHATGLM, CHATGPT, CLAUDE, DEEPSEEK};
+    use crate::utils::tokens::HeuristicTokenizer;
+
+    fn request() -> ApiRequest {
+        ApiRequest::new(Model::ChatGPT(CHATGPT::V4oMini), None).add_message(Message::user("hi"))
+    }
+
+    #[test]
+    fn cache_key_ignores_map_insertion_order() {
+        let keys: Vec<String> = (0..12).map(|i| format!("key{}", i)).collect();
+        let mut forward = HashMap::new();
+        for key in &keys {
+            forward.insert(key.clone(), "v".to_string());
+        }
+        let mut backward = HashMap::with_capacity(64);
+        for key in keys.iter().rev() {
+            backward.insert(key.clone(), "v".to_string());
+        }
+        let mut a = request();
+        a.options.metadata = Some(forward);
+        let mut b = request();
+        b.options.metadata = Some(backward);
+
+        assert_eq!(a.cache_key().unwrap(), b.cache_key().unwrap());
+        assert_ne!(a.cache_key().unwrap(), request().cache_key().unwrap());
+    }
+
+    #[test]
+    fn estimate_max_cost_prices_prompt_and_output_cap() {
+        let mut request = ApiRequest::new(Model::ChatGPT(CHATGPT::V4o), None)
+            .add_message(Message::user("abcdefgh"));
+        request.options.max_tokens = Some(1000);
+
+        let cost = request.estimate_max_cost(&HeuristicTokenizer).unwrap();
+        // 4 tokens of message overhead plus 2 of text, at $2.50/M input and
+        // 1000 output tokens at $10/M.
+        assert!((cost.input - 6.0 * 2.5 / 1_000_000.0).abs() < 1e-12);
+        assert!((cost.output - 0.01).abs() < 1e-12);
+
+        let unpriced = ApiRequest::new(Model::ChatGLM(CHATGLM::Glm4Flash), None);
+        assert!(unpriced.estimate_max_cost(&HeuristicTokenizer).is_none());
+    }
+
+    fn normalized_body(request: &ApiRequest) -> serde_json::Value {
+        let mut request = request.clone();
+        request.options = request.options.normalize_for(&request.model);
+        serde_json::to_value(&request).unwrap()
+    }
+
+    #[test]
+    fn reasoning_effort_is_sent_as_is_to_openai() {
+        let request = ApiRequest::new(Model::ChatGPT(CHATGPT::VO1Mini), None)
+            .with_reasoning(ReasoningEffort::High);
+        let body = normalized_body(&request);
+
+        assert_eq!(body["reasoning_effort"], "high");
+        assert!(body.get("thinking").is_none());
+    }
+
+    #[test]
+    fn reasoning_effort_becomes_an_anthropic_thinking_budget() {
+        // Chosen before the model, so the provider is only known at send time.
+        let mut request = ApiRequest::new(Model::ChatGPT(CHATGPT::VO1Mini), None)
+            .with_reasoning(ReasoningEffort::Low);
+        request.model = Model::Claude(CLAUDE::Sonnet3_7);
+        request.options = request.options.normalize_for(&request.model);
+        let body = crate::api::anthropic::request_body(&request);
+
+        assert_eq!(
+            body["thinking"],
+            serde_json::json!({ "type": "enabled", "budget_tokens": 1024 })
+        );
+        assert!(body.get("reasoning_effort").is_none());
+    }
+
+    #[test]
+    fn reasoning_effort_is_dropped_for_providers_without_it() {
+        let request = ApiRequest::new(Model::Deepseek(DEEPSEEK::R1Official), None)
+            .with_reasoning(ReasoningEffort::Medium);
+        let body = normalized_body(&request);
+
+        assert!(body.get("reasoning_effort").is_none());
+        assert!(body.get("thinking").is_none());
+    }
+
+    #[test]
+    fn high_effort_leaves_room_above_the_anthropic_thinking_budget() {
+        let mut request = ApiRequest::new(Model::Claude(CLAUDE::Sonnet3_7), None)
+            .add_message(Message::user("hi"))
+            .with_reasoning(ReasoningEffort::High);
+        request.options.temperature = Some(0.5);
+        request.options.top_p = Some(0.75);
+        request.options = request.options.normalize_for(&request.model);
+        let body = crate::api::anthropic::request_body(&request);
+
+        assert_eq!(body["thinking"]["budget_tokens"], 16384);
+        assert!(body["max_tokens"].as_u64().unwrap() > 16384);
+        assert!(body.get("temperature").is_none());
+        assert!(body.get("top_p").is_none());
+    }
+
+    #[test]
+    fn reasoning_effort_is_dropped_for_claude_models_without_thinking() {
+        let mut request = ApiRequest::new(Model::Claude(CLAUDE::Haiku3_5), None)
+            .add_message(Message::user("hi"))
+            .with_reasoning(ReasoningEffort::High);
+        request.options.temperature = Some(0.5);
+        request.options = request.options.normalize_for(&request.model);
+        let body = crate::api::anthropic::request_body(&request);
+
+        assert!(body.get("thinking").is_none());
+        assert_eq!(body["max_tokens"], 8192);
+        assert_eq!(body["temperature"], 0.5);
+    }
+
+    fn sampled(model: Model) -> ApiRequest {
+        ApiRequest::new(model, None).with_options(RequestOptions {
+            temperature: Some(0.5),
+            top_p: Some(0.75),
+            max_tokens: Some(256),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn reasoning_models_get_an_adjusted_body() {
+        let body = normalized_body(&sampled(Model::ChatGPT(CHATGPT::VO1Mini)));
+        assert!(body.get("temperature").is_none());
+        assert!(body.get("top_p").is_none());
+        assert!(body.get("max_tokens").is_none());
+        assert_eq!(body["max_completion_tokens"], 256);
+
+        // Only OpenAI renames the token cap.
+        let body = normalized_body(&sampled(Model::Deepseek(DEEPSEEK::R1Official)));
+        assert!(body.get("temperature").is_none());
+        assert!(body.get("top_p").is_none());
+        assert_eq!(body["max_tokens"], 256);
+
+        let body = normalized_body(&sampled(Model::ChatGPT(CHATGPT::V4oMini)));
+        assert_eq!(body["temperature"], 0.5);
+        assert_eq!(body["top_p"], 0.75);
+        assert_eq!(body["max_tokens"], 256);
+    }
+
+    #[test]
+    fn normalize_for_drops_top_k_for_openai_only() {
+        let options = RequestOptions {
+            top_k: Some(40),
+            ..Default::default()
+        };
+        let openai = options.normalize_for(&Model::ChatGPT(CHATGPT::V4oMini));
+        assert_eq!(openai.top_k, None);
+        let anthropic = options.normalize_for(&Model::Claude(CLAUDE::Sonnet3_7));
+        assert_eq!(anthropic.top_k, Some(40));
+    }
+
+    #[test]
+    fn normalize_for_keeps_stop_for_anthropic_as_stop_sequences() {
+        let mut request = ApiRequest::new(Model::Claude(CLAUDE::Sonnet3_7), None)
+            .add_message(Message::user("hi"));
+        request.options.stop = Some(vec!["END".to_string()]);
+        request.options = request.options.normalize_for(&request.model);
+        let body = crate::api::anthropic::request_body(&request);
+
+        assert_eq!(body["stop_sequences"], serde_json::json!(["END"]));
+        assert!(body.get("stop").is_none());
+    }
+
+    fn with_metadata(entries: impl IntoIterator<Item = (String, String)>) -> ApiRequest {
+        let mut request = request();
+        request.options.metadata = Some(entries.into_iter().collect());
+        request
+    }
+
+    #[test]
+    fn validate_accepts_metadata_within_the_limits() {
+        let entries = (0..MAX_METADATA_ENTRIES).map(|i| {
+            (
+                format!("{:0>width$}", i, width = MAX_METADATA_KEY_CHARS),
+                "v".repeat(MAX_METADATA_VALUE_CHARS),
+            )
+        });
+        assert!(with_metadata(entries).validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_metadata_beyond_the_limits() {
+        let too_many = (0..=MAX_METADATA_ENTRIES).map(|i| (format!("k{}", i), "v".to_string()));
+        let long_key = [("k".repeat(MAX_METADATA_KEY_CHARS + 1), "v".to_string())];
+        let long_value = [("k".to_string(), "v".repeat(MAX_METADATA_VALUE_CHARS + 1))];
+
+        for request in [
+            with_metadata(too_many),
+            with_metadata(long_key),
+            with_metadata(long_value),
+        ] {
+            assert!(matches!(
+                request.validate(),
+                Err(LlmHubError::ConfigError(_))
+            ));
+        }
+    }
 }
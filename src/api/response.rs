@@ -1,23 +1,47 @@
-use crate::api::message::Message;
+use crate::api::message::{Message, Role};
+use crate::utils::error::{LlmHubError, Result};
+use futures::stream::{self, BoxStream, StreamExt};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, PickFirst, serde_as};
 
 // --- Supporting Structs ---
 
+// Token counts accept numeric strings (`"13"`) as well as numbers, since some
+// OpenAI-compatible providers serialize them that way.
+
+#[serde_as]
 #[derive(Debug, Deserialize, Clone, Default)]
 pub struct Usage {
+    #[serde_as(as = "Option<PickFirst<(_, DisplayFromStr)>>")]
     pub prompt_tokens: Option<u32>,
+    #[serde_as(as = "Option<PickFirst<(_, DisplayFromStr)>>")]
     pub completion_tokens: Option<u32>,
+    #[serde_as(as = "Option<PickFirst<(_, DisplayFromStr)>>")]
     pub total_tokens: Option<u32>,
     pub prompt_tokens_details: Option<PromptTokensDetails>,
+    pub completion_tokens_details: Option<CompletionTokensDetails>,
+    #[serde_as(as = "Option<PickFirst<(_, DisplayFromStr)>>")]
     pub prompt_cache_hit_tokens: Option<u32>,
+    #[serde_as(as = "Option<PickFirst<(_, DisplayFromStr)>>")]
     pub prompt_cache_miss_tokens: Option<u32>,
 }
 
+#[serde_as]
 #[derive(Debug, Deserialize, Clone, Default)]
 pub struct PromptTokensDetails {
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
     pub cached_tokens: u32,
 }
 
+#[serde_as]
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct CompletionTokensDetails {
+    #[serde(default)]
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    pub reasoning_tokens: u32,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ToolCallFunction {
     pub name: Option<String>,
@@ -26,12 +50,41 @@ pub struct ToolCallFunction {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ToolCall {
+    /// Position of the call within the message; only present on streamed deltas.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<u32>,
     pub id: Option<String>,
     #[serde(rename = "type")]
     pub tool_type: Option<String>,
     pub function: Option<ToolCallFunction>,
 }
 
+/// Why a choice stopped generating, normalized across providers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FinishReason {
+    Stop,
+    Length,
+    ToolCalls,
+    ContentFilter,
+    FunctionCall,
+    Other(String),
+}
+
+/// Accepts both OpenAI `finish_reason` values and Anthropic `stop_reason`
+/// values (`end_turn`, `stop_sequence`, `max_tokens`, `tool_use`).
+impl From<&str> for FinishReason {
+    fn from(reason: &str) -> Self {
+        match reason {
+            "stop" | "end_turn" | "stop_sequence" => Self::Stop,
+            "length" | "max_tokens" => Self::Length,
+            "tool_calls" | "tool_use" => Self::ToolCalls,
+            "content_filter" => Self::ContentFilter,
+            "function_call" => Self::FunctionCall,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
 // --- Non-Streaming Response ---
 
 #[derive(Debug, Deserialize, Clone)]
@@ -45,12 +98,162 @@ pub struct ApiResponse {
     pub system_fingerprint: Option<String>,
 }
 
+/// A provider's assessment of one harm category for a response.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SafetyRating {
+    /// The harm category, e.g. `HARM_CATEGORY_HARASSMENT`.
+    pub category: String,
+    /// The likelihood of harm, e.g. `NEGLIGIBLE`, `LOW`, `MEDIUM` or `HIGH`.
+    pub probability: String,
+    /// Whether the content was blocked because of this rating.
+    #[serde(default)]
+    pub blocked: Option<bool>,
+}
+
+impl ApiResponse {
+    /// Returns the backend `system_fingerprint`, which changes when the
+    /// provider deploys a different backend configuration.
+    pub fn fingerprint(&self) -> Option<&str> {
+        self.system_fingerprint.as_deref()
+    }
+
+    /// Returns the first choice, if any.
+    pub fn first_choice(&self) -> Option<&ApiChoice> {
+        self.choices.first()
+    }
+
+    /// Returns the text content of the first choice, if any.
+    pub fn content(&self) -> Option<&str> {
+        self.first_choice()?.message.content.as_ref()?.as_text()
+    }
+
+    /// Deserializes the first choice's content as JSON, for use with
+    /// `json_object` response formats.
+    ///
+    /// Content that fails to parse because generation stopped at the token
+    /// limit is reported as `TruncatedResponse` rather than a parse error.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        let content = self
+            .content()
+            .ok_or_else(|| LlmHubError::ApiError("Response has no content".to_string()))?;
+        serde_json::from_str(content).map_err(|e| {
+            match self.first_choice().and_then(ApiChoice::finish_reason) {
+                Some(FinishReason::Length) => LlmHubError::TruncatedResponse(content.to_string()),
+                _ => LlmHubError::from(e),
+            }
+        })
+    }
+
+    /// Converts the response into a single synthetic chunk carrying every
+    /// choice's full message as its delta.
+    pub fn into_chunk(self) -> StreamChunk {
+        let choices = self
+            .choices
+            .into_iter()
+            .map(|choice| StreamChoice {
+                index: choice.index,
+                delta: StreamDelta {
+                    role: Some(role_name(&choice.message.role).to_string()),
+                    content: choice.message.content.map(|c| c.text().into_owned()),
+                    reasoning_content: None,
+                    tool_calls: choice.message.tool_calls,
+                },
+                finish_reason: choice.finish_reason,
+                logprobs: choice.logprobs,
+            })
+            .collect();
+        StreamChunk {
+            id: self.id,
+            object: "chat.completion.chunk".to_string(),
+            created: self.created,
+            model: self.model,
+            choices,
+            usage: self.usage,
+            system_fingerprint: self.system_fingerprint,
+        }
+    }
+
+    /// Replays the response as a one-chunk stream, so a cached full response
+    /// can feed the same consumer as [`Client::chat_stream`](crate::api::client::Client::chat_stream).
+    pub fn into_stream(self) -> BoxStream<'static, Result<StreamChunk>> {
+        stream::once(async move { Ok(self.into_chunk()) }).boxed()
+    }
+}
+
+fn role_name(role: &Role) -> &'static str {
+    match role {
+        Role::System => "system",
+        Role::User => "user",
+        Role::Assistant => "assistant",
+        Role::Tool => "tool",
+    }
+}
+
+/// Indexes into `choices`, panicking with the number of available choices
+/// when out of range. Use [`ApiResponse::first_choice`] or `choices.get` for
+/// a non-panicking lookup.
+impl std::ops::Index<usize> for ApiResponse {
+    type Output = ApiChoice;
+
+    fn index(&self, index: usize) -> &ApiChoice {
+        self.choices.get(index).unwrap_or_else(|| {
+            panic!(
+                "choice index {} out of range: response has {} choices",
+                index,
+                self.choices.len()
+            )
+        })
+    }
+}
+
+impl<'a> IntoIterator for &'a ApiResponse {
+    type Item = &'a ApiChoice;
+    type IntoIter = std::slice::Iter<'a, ApiChoice>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.choices.iter()
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct ApiChoice {
     pub index: i32,
     pub message: Message,
     pub finish_reason: Option<String>,
     pub logprobs: Option<serde_json::Value>,
+    /// Safety classifications of this choice, for providers that report them.
+    ///
+    /// Gemini only includes these in its native `generateContent` responses;
+    /// its OpenAI-compatible endpoint, which is what `ApiProvider::GOOGLE`
+    /// uses, omits them, so this is `None` for Gemini models.
+    #[serde(default, alias = "safetyRatings")]
+    pub safety_ratings: Option<Vec<SafetyRating>>,
+}
+
+impl ApiChoice {
+    /// Returns the finish reason normalized across providers.
+    pub fn finish_reason(&self) -> Option<FinishReason> {
+        self.finish_reason.as_deref().map(FinishReason::from)
+    }
+}
+
+// --- Model Listing ---
+
+/// A model returned by a provider's models endpoint.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ModelInfo {
+    pub id: String,
+    /// Usually `"model"`; empty when the provider omits it.
+    #[serde(default)]
+    pub object: String,
+    pub created: Option<i64>,
+    pub owned_by: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ModelList {
+    pub data: Vec<ModelInfo>,
 }
 
 // --- Streaming Response ---
@@ -66,6 +269,38 @@ pub struct StreamChunk {
     pub system_fingerprint: Option<String>,
 }
 
+impl StreamChunk {
+    /// Returns the first choice, if any.
+    pub fn first_choice(&self) -> Option<&StreamChoice> {
+        self.choices.first()
+    }
+}
+
+/// Indexes into `choices`, panicking with the number of available choices
+/// when out of range.
+impl std::ops::Index<usize> for StreamChunk {
+    type Output = StreamChoice;
+
+    fn index(&self, index: usize) -> &StreamChoice {
+        self.choices.get(index).unwrap_or_else(|| {
+            panic!(
+                "choice index {} out of range: chunk has {} choices",
+                index,
+                self.choices.len()
+            )
+        })
+    }
+}
+
+impl<'a> IntoIterator for &'a StreamChunk {
+    type Item = &'a StreamChoice;
+    type IntoIter = std::slice::Iter<'a, StreamChoice>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.choices.iter()
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct StreamChoice {
     pub index: i32,
@@ -81,3 +316,79 @@ pub struct StreamDelta {
     pub reasoning_content: Option<String>,
     pub tool_calls: Option<Vec<ToolCall>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usage_accepts_numeric_strings() {
+        let usage: Usage = serde_json::from_value(serde_json::json!({
+            "prompt_tokens": "13",
+            "completion_tokens": 7,
+            "total_tokens": "20",
+            "prompt_tokens_details": { "cached_tokens": "5" },
+            "completion_tokens_details": { "reasoning_tokens": "3" }
+        }))
+        .unwrap();
+
+        assert_eq!(usage.prompt_tokens, Some(13));
+        assert_eq!(usage.completion_tokens, Some(7));
+        assert_eq!(usage.total_tokens, Some(20));
+        assert_eq!(usage.prompt_tokens_details.unwrap().cached_tokens, 5);
+        assert_eq!(usage.completion_tokens_details.unwrap().reasoning_tokens, 3);
+    }
+
+    #[test]
+    fn anthropic_stop_reasons_normalize() {
+        assert_eq!(FinishReason::from("end_turn"), FinishReason::Stop);
+        assert_eq!(FinishReason::from("stop_sequence"), FinishReason::Stop);
+        assert_eq!(FinishReason::from("max_tokens"), FinishReason::Length);
+        assert_eq!(FinishReason::from("tool_use"), FinishReason::ToolCalls);
+        assert_eq!(
+            FinishReason::from("refusal"),
+            FinishReason::Other("refusal".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn into_stream_replays_the_content_as_one_chunk() {
+        let response: ApiResponse = serde_json::from_value(serde_json::json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1,
+            "model": "gpt-4o",
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": "cached answer" },
+                "finish_reason": "stop"
+            }]
+        }))
+        .unwrap();
+
+        let chunks: Vec<StreamChunk> = response
+            .into_stream()
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].id, "chatcmpl-1");
+        let choice = &chunks[0].choices[0];
+        assert_eq!(choice.delta.role.as_deref(), Some("assistant"));
+        assert_eq!(choice.delta.content.as_deref(), Some("cached answer"));
+        assert_eq!(choice.finish_reason.as_deref(), Some("stop"));
+    }
+
+    #[test]
+    fn choices_accept_either_safety_ratings_spelling() {
+        let choice: ApiChoice = serde_json::from_value(serde_json::json!({
+            "index": 0,
+            "message": { "role": "assistant", "content": "hi" },
+            "finish_reason": "stop",
+            "logprobs": null,
+            "safetyRatings": [{ "category": "HARM_CATEGORY_HATE_SPEECH", "probability": "LOW" }]
+        }))
+        .unwrap();
+        assert_eq!(choice.safety_ratings.unwrap()[0].probability, "LOW");
+    }
+}
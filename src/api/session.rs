@@ -1,10 +1,25 @@
 use uuid::Uuid;
-use crate::api::message::Message;
+use crate::api::message::{Message, Role};
+use crate::api::response::ApiResponse;
+use crate::utils::error::{LlmHubError, Result};
+use crate::utils::tokens::{HeuristicTokenizer, Tokenizer};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
+#[derive(Serialize, Deserialize)]
 pub struct Session {
     id: String,
     messages: Vec<Message>,
     max_history: usize,
+    #[serde(default)]
+    token_budget: Option<usize>,
+    /// Not persisted; a loaded session uses the heuristic tokenizer.
+    #[serde(skip, default = "default_tokenizer")]
+    tokenizer: Arc<dyn Tokenizer>,
+}
+
+fn default_tokenizer() -> Arc<dyn Tokenizer> {
+    Arc::new(HeuristicTokenizer)
 }
 
 impl Session {
@@ -13,37 +28,211 @@ impl Session {
             id: Uuid::new_v4().to_string(),
             messages: Vec::new(),
             max_history: 20,
+            token_budget: None,
+            tokenizer: default_tokenizer(),
         }
     }
 
+    /// Restores a session saved with [`Session::save_to_file`], keeping its
+    /// original id. A file that isn't a valid session is a `SessionError`.
+    pub fn load_from_file(path: &str) -> Result<Session> {
+        let content = std::fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|e| {
+            LlmHubError::SessionError(format!("Malformed session file {}: {}", path, e))
+        })
+    }
+
+    /// Saves the id, messages and history limit as JSON.
+    pub fn save_to_file(&self, path: &str) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Replaces the tokenizer used by [`Session::estimated_tokens`].
+    pub fn with_tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
     pub fn with_max_history(mut self, max: usize) -> Self {
         self.max_history = max;
         self
     }
 
+    /// Caps the history at `max_tokens` as counted by the session's
+    /// tokenizer. Once over budget, the oldest non-system messages are
+    /// dropped (with any tool results left without their call) until the
+    /// history fits; system messages and the newest message are kept.
+    pub fn with_token_budget(mut self, max_tokens: usize) -> Self {
+        self.token_budget = Some(max_tokens);
+        self.truncate_history();
+        self
+    }
+
+    /// Forks the session: the copy has a fresh id, the same messages and
+    /// settings, and evolves independently of `self`. Combine with
+    /// [`Session::truncate_to`] to branch from an earlier turn.
+    pub fn branch(&self) -> Session {
+        self.branch_at(self.messages.len())
+    }
+
+    /// Forks the session keeping only the first `index` messages.
+    pub fn branch_at(&self, index: usize) -> Session {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            messages: self.messages[..index.min(self.messages.len())].to_vec(),
+            max_history: self.max_history,
+            token_budget: self.token_budget,
+            tokenizer: self.tokenizer.clone(),
+        }
+    }
+
+    /// Rolls the history back to its first `len` messages. Does nothing if
+    /// the history is already that short.
+    pub fn truncate_to(&mut self, len: usize) {
+        self.messages.truncate(len);
+    }
+
     pub fn add_message(&mut self, message: Message) {
         self.messages.push(message);
         self.truncate_history();
     }
 
+    /// Replaces every system message with a single one at the start of the history.
+    pub fn replace_system(&mut self, content: impl Into<String>) {
+        self.messages.retain(|m| m.role != Role::System);
+        self.messages.insert(0, Message::system(content));
+    }
+
+    /// Appends the first choice's assistant message, including any tool calls,
+    /// from a chat response.
+    pub fn append_response(&mut self, response: &ApiResponse) {
+        if let Some(choice) = response.choices.first() {
+            self.add_message(choice.message.clone());
+        }
+    }
+
+    /// Enforces `max_history` and the token budget by dropping the oldest
+    /// non-system messages; system messages are never removed.
     fn truncate_history(&mut self) {
-        if self.max_history > 0 && self.messages.len() > self.max_history {
-            let to_remove = self.messages.len() - self.max_history;
-            self.messages.drain(0..to_remove);
+        if self.max_history > 0 {
+            while self.messages.len() > self.max_history && self.drop_oldest() {}
+        }
+        if let Some(budget) = self.token_budget {
+            while self.estimated_tokens() > budget && self.drop_oldest() {}
         }
     }
 
+    /// Removes the oldest non-system message other than the newest one,
+    /// along with any tool results it leaves without their call. Returns
+    /// false when there is nothing left to drop.
+    fn drop_oldest(&mut self) -> bool {
+        let last = self.messages.len().saturating_sub(1);
+        let Some(oldest) = self.messages[..last]
+            .iter()
+            .position(|m| m.role != Role::System)
+        else {
+            return false;
+        };
+        self.messages.remove(oldest);
+        while oldest < self.messages.len() - 1 && self.messages[oldest].role == Role::Tool {
+            self.messages.remove(oldest);
+        }
+        true
+    }
+
     pub fn get_messages(&self) -> &Vec<Message> {
         &self.messages
     }
 
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Message> {
+        self.messages.iter()
+    }
+
+    pub fn last(&self) -> Option<&Message> {
+        self.messages.last()
+    }
+
     pub fn id(&self) -> &str {
         &self.id
     }
+
+    /// Estimates the number of tokens the current history occupies.
+    pub fn estimated_tokens(&self) -> usize {
+        self.tokenizer.count_messages(&self.messages)
+    }
+
+    /// Logs a warning when the estimated history size reaches 90% of `limit`,
+    /// returning whether it did.
+    pub fn warn_if_over(&self, limit: usize) -> bool {
+        let estimated = self.estimated_tokens();
+        let over = estimated * 10 >= limit * 9;
+        if over {
+            log::warn!(
+                "Session {} is using ~{} of {} context tokens",
+                self.id,
+                estimated,
+                limit
+            );
+        }
+        over
+    }
 }
 
 impl Default for Session {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(session: &Session) -> Vec<String> {
+        session
+            .iter()
+            .map(|m| m.text().unwrap_or_default().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn branches_evolve_independently() {
+        let mut session = Session::new();
+        session.add_message(Message::user("one"));
+        session.add_message(Message::assistant("two"));
+
+        let mut branch = session.branch();
+        assert_ne!(branch.id(), session.id());
+        assert_eq!(texts(&branch), texts(&session));
+
+        branch.add_message(Message::user("branch"));
+        session.add_message(Message::user("original"));
+        assert_eq!(texts(&session), ["one", "two", "original"]);
+        assert_eq!(texts(&branch), ["one", "two", "branch"]);
+
+        branch.truncate_to(1);
+        assert_eq!(texts(&branch.branch()), ["one"]);
+        assert_eq!(texts(&session), ["one", "two", "original"]);
+    }
+
+    #[test]
+    fn branch_at_keeps_the_leading_messages() {
+        let mut session = Session::new();
+        session.add_message(Message::user("one"));
+        session.add_message(Message::assistant("two"));
+
+        let mut branch = session.branch_at(1);
+        branch.add_message(Message::assistant("other"));
+        assert_eq!(texts(&branch), ["one", "other"]);
+        assert_eq!(texts(&session), ["one", "two"]);
+        assert_eq!(session.branch_at(10).len(), 2);
+    }
+}
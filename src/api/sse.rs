@@ -0,0 +1,200 @@
+use crate::utils::error::{LlmHubError, Result};
+
+/// Default cap on a single SSE line or event payload: 8 MiB.
+pub const DEFAULT_MAX_SSE_LINE_BYTES: usize = 8 * 1024 * 1024;
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// An incremental decoder for `text/event-stream` bodies.
+///
+/// Bytes are fed as they arrive and the `data` payloads of completed events
+/// are returned. Lines may end in CRLF, LF or a lone CR, and a leading UTF-8
+/// byte order mark is skipped, as the SSE spec requires. A line or event
+/// payload longer than `max_line_bytes` is an error, so a server that never
+/// sends a newline can't grow the buffer without bound.
+#[derive(Debug)]
+pub struct SseDecoder {
+    buffer: Vec<u8>,
+    data: Option<String>,
+    max_line_bytes: usize,
+    /// Whether the start of the stream (and any BOM) has been seen.
+    started: bool,
+    /// Whether the last line ended in a CR at the end of a chunk, so an LF
+    /// opening the next chunk completes that CRLF rather than a blank line.
+    after_cr: bool,
+}
+
+impl SseDecoder {
+    pub fn new(max_line_bytes: usize) -> Self {
+        Self {
+            buffer: Vec::new(),
+            data: None,
+            max_line_bytes,
+            started: false,
+            after_cr: false,
+        }
+    }
+
+    /// Feeds a chunk of the body, returning the payloads of any events it
+    /// completes.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<Vec<String>> {
+        let mut events = Vec::new();
+        let mut scanned = self.buffer.len();
+        self.buffer.extend_from_slice(chunk);
+
+        if !self.started {
+            if self.buffer.len() < BOM.len() && BOM.starts_with(&self.buffer) {
+                return Ok(events);
+            }
+            if self.buffer.starts_with(BOM) {
+                self.buffer.drain(..BOM.len());
+            }
+            self.started = true;
+        }
+        if self.after_cr && !self.buffer.is_empty() {
+            if self.buffer[0] == b'\n' {
+                self.buffer.remove(0);
+            }
+            self.after_cr = false;
+            scanned = 0;
+        }
+
+        while let Some(offset) = self.buffer[scanned..]
+            .iter()
+            .position(|b| matches!(b, b'\r' | b'\n'))
+        {
+            let end = scanned + offset;
+            let terminator = match self.buffer.get(end..end + 2) {
+                Some(b"\r\n") => 2,
+                None if self.buffer[end] == b'\r' => {
+                    self.after_cr = true;
+                    1
+                }
+                _ => 1,
+            };
+            let line: Vec<u8> = self.buffer.drain(..end + terminator).take(end).collect();
+            scanned = 0;
+            self.process_line(&String::from_utf8_lossy(&line), &mut events)?;
+        }
+
+        if self.buffer.len() > self.max_line_bytes {
+            return Err(self.too_long());
+        }
+        Ok(events)
+    }
+
+    /// Returns the payload of a final event the body didn't terminate with a
+    /// blank line.
+    pub fn finish(&mut self) -> Option<String> {
+        if !self.buffer.is_empty() {
+            let line = String::from_utf8_lossy(&std::mem::take(&mut self.buffer)).into_owned();
+            let mut events = Vec::new();
+            // The buffer is already within the limit, so this can't fail.
+            let _ = self.process_line(&line, &mut events);
+        }
+        self.data.take()
+    }
+
+    fn process_line(&mut self, line: &str, events: &mut Vec<String>) -> Result<()> {
+        if line.is_empty() {
+            events.extend(self.data.take());
+            return Ok(());
+        }
+        if line.starts_with(':') {
+            return Ok(());
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        if field == "data" {
+            let data = match self.data.as_mut() {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                    data
+                }
+                None => self.data.insert(value.to_string()),
+            };
+            if data.len() > self.max_line_bytes {
+                return Err(self.too_long());
+            }
+        }
+        Ok(())
+    }
+
+    fn too_long(&self) -> LlmHubError {
+        LlmHubError::StreamError(format!(
+            "Server-sent event exceeds the {}-byte limit",
+            self.max_line_bytes
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(chunks: &[&[u8]]) -> Vec<String> {
+        let mut decoder = SseDecoder::new(DEFAULT_MAX_SSE_LINE_BYTES);
+        let mut events = Vec::new();
+        for chunk in chunks {
+            events.extend(decoder.feed(chunk).unwrap());
+        }
+        events.extend(decoder.finish());
+        events
+    }
+
+    #[test]
+    fn reassembles_events_split_across_chunks() {
+        assert_eq!(
+            decode(&[b"da", b"ta: {\"a\":", b"1}\n", b"\ndata: two\n\n"]),
+            ["{\"a\":1}", "two"]
+        );
+    }
+
+    #[test]
+    fn joins_multi_line_data_and_skips_comments() {
+        assert_eq!(
+            decode(&[b": keep-alive\nevent: message\ndata: one\ndata: two\n\n"]),
+            ["one\ntwo"]
+        );
+    }
+
+    #[test]
+    fn accepts_crlf_lf_and_cr_line_endings() {
+        assert_eq!(
+            decode(&[b"data: a\r\n\r\ndata: b\n\ndata: c\r\rdata: d"]),
+            ["a", "b", "c", "d"]
+        );
+        // A CRLF split between chunks is one line break, not two.
+        assert_eq!(decode(&[b"data: a\r", b"\ndata: b\r", b"\n\r\n"]), ["a\nb"]);
+    }
+
+    #[test]
+    fn strips_a_leading_byte_order_mark_once() {
+        assert_eq!(decode(&[b"\xEF\xBB\xBFdata: a\n\n"]), ["a"]);
+        assert_eq!(decode(&[b"\xEF", b"\xBB", b"\xBFdata: a\n\n"]), ["a"]);
+        // Only the first bytes of the stream can be a byte order mark.
+        assert_eq!(
+            decode(&[b"\xEF\xBB\xBFdata: a\n\n", b"\xEF\xBB\xBFdata: b\n\n"]),
+            ["a"]
+        );
+    }
+
+    #[test]
+    fn rejects_lines_and_payloads_over_the_limit() {
+        let mut decoder = SseDecoder::new(8);
+        assert!(matches!(
+            decoder.feed(b"data: 0123456789"),
+            Err(LlmHubError::StreamError(_))
+        ));
+
+        let mut decoder = SseDecoder::new(8);
+        assert!(decoder.feed(b"data: 01234\n").is_ok());
+        assert!(matches!(
+            decoder.feed(b"data: 56789\n"),
+            Err(LlmHubError::StreamError(_))
+        ));
+    }
+}
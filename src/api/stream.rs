@@ -0,0 +1,330 @@
+use crate::api::message::{Content, Message, Role};
+use crate::api::response::{
+    ApiChoice, ApiResponse, FinishReason, StreamChunk, ToolCall, ToolCallFunction, Usage,
+};
+use crate::utils::error::Result;
+use futures::{Stream, StreamExt};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Default)]
+struct ChoiceState {
+    content: String,
+    reasoning_content: String,
+    tool_calls: ToolCallAccumulator,
+    finish_reason: Option<String>,
+}
+
+/// Folds streamed chunks into per-choice state as they arrive.
+///
+/// Choices are keyed by their `index`, so interleaved chunks for several
+/// choices (`n > 1`) accumulate independently.
+#[derive(Debug, Clone, Default)]
+pub struct StreamAccumulator {
+    id: String,
+    model: String,
+    created: i64,
+    choices: BTreeMap<i32, ChoiceState>,
+    usage: Option<Usage>,
+    system_fingerprint: Option<String>,
+}
+
+impl StreamAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merges a chunk into the accumulated state.
+    pub fn push(&mut self, chunk: &StreamChunk) {
+        if self.id.is_empty() {
+            self.id = chunk.id.clone();
+            self.model = chunk.model.clone();
+            self.created = chunk.created;
+        }
+        if chunk.usage.is_some() {
+            self.usage = chunk.usage.clone();
+        }
+        if let Some(fingerprint) = &chunk.system_fingerprint {
+            self.system_fingerprint = Some(fingerprint.clone());
+        }
+        for choice in &chunk.choices {
+            let state = self.choices.entry(choice.index).or_default();
+            if let Some(content) = &choice.delta.content {
+                state.content.push_str(content);
+            }
+            if let Some(reasoning) = &choice.delta.reasoning_content {
+                state.reasoning_content.push_str(reasoning);
+            }
+            if let Some(tool_calls) = &choice.delta.tool_calls {
+                state.tool_calls.push(tool_calls);
+            }
+            if choice.finish_reason.is_some() {
+                state.finish_reason = choice.finish_reason.clone();
+            }
+        }
+    }
+
+    /// Returns true if no content, reasoning, tool call or finish reason has
+    /// arrived, which distinguishes a stream that ended prematurely from a
+    /// legitimate empty completion (which still reports a finish reason).
+    pub fn is_empty(&self) -> bool {
+        self.choices.values().all(|s| {
+            s.content.is_empty()
+                && s.reasoning_content.is_empty()
+                && s.tool_calls.is_empty()
+                && s.finish_reason.is_none()
+        })
+    }
+
+    /// Returns the number of distinct choices seen so far.
+    pub fn choice_count(&self) -> usize {
+        self.choices.len()
+    }
+
+    /// Iterates over `(index, content)` for every choice, in index order.
+    pub fn contents(&self) -> impl Iterator<Item = (i32, &str)> {
+        self.choices
+            .iter()
+            .map(|(index, state)| (*index, state.content.as_str()))
+    }
+
+    /// Returns the last `system_fingerprint` seen in the stream.
+    pub fn fingerprint(&self) -> Option<&str> {
+        self.system_fingerprint.as_deref()
+    }
+
+    /// Returns the content accumulated so far for a choice.
+    pub fn content(&self, index: i32) -> Option<&str> {
+        self.choices.get(&index).map(|s| s.content.as_str())
+    }
+
+    /// Returns the reasoning content accumulated so far for a choice.
+    pub fn reasoning_content(&self, index: i32) -> Option<&str> {
+        self.choices
+            .get(&index)
+            .map(|s| s.reasoning_content.as_str())
+    }
+
+    /// Returns the last finish reason reported for a choice, available as
+    /// soon as the chunk carrying it is pushed.
+    pub fn finish_reason(&self, index: i32) -> Option<FinishReason> {
+        let reason = self.choices.get(&index)?.finish_reason.as_deref()?;
+        Some(FinishReason::from(reason))
+    }
+
+    /// Returns the tool calls assembled so far for a choice.
+    pub fn tool_calls(&self, index: i32) -> Vec<ToolCall> {
+        self.choices
+            .get(&index)
+            .map(|s| s.tool_calls.calls())
+            .unwrap_or_default()
+    }
+
+    /// Builds the response a non-streaming request would have returned, with
+    /// one assistant message per choice in index order and the last usage
+    /// report seen. Reasoning content is not part of the message.
+    pub fn into_response(self) -> ApiResponse {
+        let choices = self
+            .choices
+            .into_iter()
+            .map(|(index, state)| {
+                let tool_calls = state.tool_calls.finish();
+                let message = Message {
+                    role: Role::Assistant,
+                    content: (!state.content.is_empty() || tool_calls.is_empty())
+                        .then_some(Content::Text(state.content)),
+                    tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+                    tool_call_id: None,
+                };
+                ApiChoice {
+                    index,
+                    message,
+                    finish_reason: state.finish_reason,
+                    logprobs: None,
+                    safety_ratings: None,
+                }
+            })
+            .collect();
+        ApiResponse {
+            id: self.id,
+            object: "chat.completion".to_string(),
+            created: self.created,
+            model: self.model,
+            choices,
+            usage: self.usage,
+            system_fingerprint: self.system_fingerprint,
+        }
+    }
+}
+
+/// Folds a chat stream into a single [`ApiResponse`], as if the request had
+/// been sent without streaming. The first error in the stream is returned.
+pub async fn collect_stream(
+    stream: impl Stream<Item = Result<StreamChunk>>,
+) -> Result<ApiResponse> {
+    let mut stream = std::pin::pin!(stream);
+    let mut accumulator = StreamAccumulator::new();
+    while let Some(chunk) = stream.next().await {
+        accumulator.push(&chunk?);
+    }
+    Ok(accumulator.into_response())
+}
+
+/// Merges streamed tool-call fragments into complete calls.
+///
+/// The first fragment of a call carries its `id` and function `name`; later
+/// fragments with the same `index` append to its `arguments`. Fragments
+/// without an `index` are matched by `id`, and an `id`-less, `index`-less
+/// fragment continues the most recent call.
+#[derive(Debug, Clone, Default)]
+pub struct ToolCallAccumulator {
+    calls: BTreeMap<u32, ToolCall>,
+}
+
+impl ToolCallAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merges the tool-call fragments of a delta.
+    pub fn push(&mut self, deltas: &[ToolCall]) {
+        self.push_with(deltas, |_, _, _| {});
+    }
+
+    /// Merges the tool-call fragments of a delta, invoking `on_fragment` with
+    /// `(index, name, arguments_delta)` for each one as it is merged, so a UI
+    /// can show a call's arguments while they are still being generated.
+    pub fn push_with(
+        &mut self,
+        deltas: &[ToolCall],
+        mut on_fragment: impl FnMut(u32, Option<&str>, &str),
+    ) {
+        for delta in deltas {
+            let index = self.resolve_index(delta);
+            let call = self.calls.entry(index).or_insert_with(|| ToolCall {
+                index: None,
+                id: None,
+                tool_type: None,
+                function: None,
+            });
+            if delta.id.is_some() {
+                call.id = delta.id.clone();
+            }
+            if delta.tool_type.is_some() {
+                call.tool_type = delta.tool_type.clone();
+            }
+            let Some(fragment) = &delta.function else {
+                continue;
+            };
+            let function = call.function.get_or_insert(ToolCallFunction {
+                name: None,
+                arguments: None,
+            });
+            if fragment.name.is_some() {
+                function.name = fragment.name.clone();
+            }
+            let arguments_delta = fragment.arguments.as_deref().unwrap_or_default();
+            function
+                .arguments
+                .get_or_insert_with(String::new)
+                .push_str(arguments_delta);
+            on_fragment(index, function.name.as_deref(), arguments_delta);
+        }
+    }
+
+    fn resolve_index(&self, delta: &ToolCall) -> u32 {
+        if let Some(index) = delta.index {
+            return index;
+        }
+        let last = self.calls.keys().next_back().copied();
+        match &delta.id {
+            Some(id) => self
+                .calls
+                .iter()
+                .find(|(_, call)| call.id.as_ref() == Some(id))
+                .map(|(index, _)| *index)
+                .unwrap_or_else(|| last.map_or(0, |index| index + 1)),
+            None => last.unwrap_or(0),
+        }
+    }
+
+    /// Returns true if no tool-call fragment has been merged yet.
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    /// Returns the calls assembled so far, in index order.
+    pub fn calls(&self) -> Vec<ToolCall> {
+        self.calls.values().cloned().collect()
+    }
+
+    /// Returns the completed calls in index order, with the stream-only
+    /// `index` cleared. A function that streamed no arguments gets `"{}"`,
+    /// so every call's `arguments` is a complete JSON string.
+    pub fn finish(self) -> Vec<ToolCall> {
+        self.calls
+            .into_values()
+            .map(|mut call| {
+                call.index = None;
+                if let Some(function) = &mut call.function {
+                    let arguments = function.arguments.get_or_insert_with(String::new);
+                    if arguments.trim().is_empty() {
+                        *arguments = "{}".to_string();
+                    }
+                }
+                call
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fragment(index: u32, name: Option<&str>, arguments: &str) -> ToolCall {
+        ToolCall {
+            index: Some(index),
+            id: name.map(|_| format!("call_{}", index)),
+            tool_type: name.map(|_| "function".to_string()),
+            function: Some(ToolCallFunction {
+                name: name.map(str::to_string),
+                arguments: Some(arguments.to_string()),
+            }),
+        }
+    }
+
+    #[test]
+    fn push_with_reports_fragments_in_order() {
+        let mut accumulator = ToolCallAccumulator::new();
+        let mut seen = Vec::new();
+        let deltas = [
+            vec![fragment(0, Some("get_weather"), "")],
+            vec![
+                fragment(0, None, "{\"city\":"),
+                fragment(1, Some("get_time"), "{"),
+            ],
+            vec![fragment(0, None, "\"Paris\"}"), fragment(1, None, "}")],
+        ];
+        for delta in &deltas {
+            accumulator.push_with(delta, |index, name, arguments| {
+                seen.push((index, name.map(str::to_string), arguments.to_string()));
+            });
+        }
+
+        let weather = Some("get_weather".to_string());
+        let time = Some("get_time".to_string());
+        assert_eq!(
+            seen,
+            [
+                (0, weather.clone(), String::new()),
+                (0, weather.clone(), "{\"city\":".to_string()),
+                (1, time.clone(), "{".to_string()),
+                (0, weather, "\"Paris\"}".to_string()),
+                (1, time, "}".to_string()),
+            ]
+        );
+        let calls = accumulator.finish();
+        let arguments = calls[0].function.as_ref().unwrap().arguments.as_deref();
+        assert_eq!(arguments, Some("{\"city\":\"Paris\"}"));
+    }
+}
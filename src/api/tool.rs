@@ -0,0 +1,94 @@
+use crate::api::response::ToolCall;
+use crate::utils::error::{LlmHubError, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// The function a tool exposes to the model.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FunctionDefinition {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// A JSON schema describing the function's arguments.
+    pub parameters: serde_json::Value,
+}
+
+/// A tool definition sent in the request's `tools` array.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Tool {
+    #[serde(rename = "type")]
+    pub tool_type: String,
+    pub function: FunctionDefinition,
+}
+
+impl Tool {
+    /// Creates a function tool from a hand-written JSON schema.
+    pub fn function(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        parameters: serde_json::Value,
+    ) -> Self {
+        Self {
+            tool_type: "function".to_string(),
+            function: FunctionDefinition {
+                name: name.into(),
+                description: Some(description.into()),
+                parameters,
+            },
+        }
+    }
+
+    /// Creates a function tool whose parameter schema is generated from `T`.
+    ///
+    /// Fields of type `Option<_>` are optional; all others are required.
+    #[cfg(feature = "schemars")]
+    pub fn from_type<T: schemars::JsonSchema>(
+        name: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        let mut parameters = schemars::schema_for!(T).to_value();
+        if let Some(schema) = parameters.as_object_mut() {
+            schema.remove("$schema");
+            schema.remove("title");
+        }
+        Self::function(name, description, parameters)
+    }
+}
+
+impl ToolCall {
+    /// Deserializes the call's JSON `arguments` into `T`.
+    pub fn parse_arguments<T: DeserializeOwned>(&self) -> Result<T> {
+        let arguments = self
+            .function
+            .as_ref()
+            .and_then(|f| f.arguments.as_deref())
+            .ok_or_else(|| LlmHubError::ApiError("Tool call has no arguments".to_string()))?;
+        serde_json::from_str(arguments).map_err(LlmHubError::from)
+    }
+}
+
+#[cfg(all(test, feature = "schemars"))]
+mod tests {
+    use super::*;
+
+    #[allow(dead_code)]
+    #[derive(schemars::JsonSchema)]
+    struct WeatherArgs {
+        /// City to look up.
+        city: String,
+        unit: Option<String>,
+    }
+
+    #[test]
+    fn from_type_marks_option_fields_optional() {
+        let tool = Tool::from_type::<WeatherArgs>("get_weather", "Get the weather");
+        let schema = &tool.function.parameters;
+
+        assert_eq!(tool.function.name, "get_weather");
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["required"], serde_json::json!(["city"]));
+        assert_eq!(schema["properties"]["city"]["type"], "string");
+        assert!(schema["properties"].get("unit").is_some());
+        assert!(schema.get("$schema").is_none());
+    }
+}
@@ -1,10 +1,10 @@
 use crate::api::providers::ApiProvider;
 use serde::{Serialize, Serializer};
-use strum_macros::{Display, EnumString};
+use strum_macros::{AsRefStr, Display, EnumString};
 
 // --- Model-specific Enums ---
 
-#[derive(Debug, Clone, PartialEq, Display, EnumString)]
+#[derive(Debug, Clone, PartialEq, Display, EnumString, AsRefStr)]
 pub enum CHATGLM {
     #[strum(serialize = "glm-4-plus")]
     Glm4Plus,
@@ -53,7 +53,7 @@ impl CHATGLM {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Display, EnumString)]
+#[derive(Debug, Clone, PartialEq, Display, EnumString, AsRefStr)]
 pub enum CHATGPT {
     #[strum(serialize = "gpt-4o")]
     V4o,
@@ -70,7 +70,7 @@ impl CHATGPT {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Display, EnumString)]
+#[derive(Debug, Clone, PartialEq, Display, EnumString, AsRefStr)]
 pub enum CLAUDE {
     #[strum(serialize = "claude-3-5-haiku-20241022")]
     Haiku3_5,
@@ -87,7 +87,7 @@ impl CLAUDE {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Display, EnumString)]
+#[derive(Debug, Clone, PartialEq, Display, EnumString, AsRefStr)]
 pub enum DEEPSEEK {
     #[strum(serialize = "deepseek-chat")]
     V3Official,
@@ -127,7 +127,7 @@ impl DEEPSEEK {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Display, EnumString)]
+#[derive(Debug, Clone, PartialEq, Display, EnumString, AsRefStr)]
 pub enum GROK {
     #[strum(serialize = "grok-2-latest")]
     Grok2Latest,
@@ -138,7 +138,7 @@ impl GROK {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Display, EnumString)]
+#[derive(Debug, Clone, PartialEq, Display, EnumString, AsRefStr)]
 pub enum QWEN {
     #[strum(serialize = "qwen2.5-72b-instruct")]
     Qwen25_7BInstruct,
@@ -153,7 +153,7 @@ impl QWEN {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Display, EnumString)]
+#[derive(Debug, Clone, PartialEq, Display, EnumString, AsRefStr)]
 pub enum DOUBAO {
     #[strum(serialize = "doubao-1-5-pro-32k-250115")]
     Doubao1_5Pro32k250115,
@@ -164,7 +164,7 @@ impl DOUBAO {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Display, EnumString)]
+#[derive(Debug, Clone, PartialEq, Display, EnumString, AsRefStr)]
 pub enum GEMINI {
     #[strum(serialize = "gemini-2.5-flash")]
     Flash2_5,
@@ -173,10 +173,36 @@ pub enum GEMINI {
 }
 impl GEMINI {
     pub fn provider(&self) -> ApiProvider {
-        ApiProvider::Volcengine
+        ApiProvider::GOOGLE
     }
 }
 
+// --- Model Families ---
+
+/// The family a model belongs to, independent of the provider serving it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display)]
+pub enum ModelFamily {
+    Deepseek,
+    GPT,
+    Claude,
+    GLM,
+    Qwen,
+    Grok,
+    Doubao,
+    Gemini,
+}
+
+/// The kind of output a model produces, which determines the endpoint it
+/// can be used with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum ModelKind {
+    Chat,
+    Embedding,
+    Image,
+    Video,
+}
+
 // --- Top-level Model Enum ---
 
 #[derive(Debug, Clone, PartialEq)]
@@ -188,6 +214,7 @@ pub enum Model {
     Grok(GROK),
     Qwen(QWEN),
     Doubao(DOUBAO),
+    Gemini(GEMINI),
 }
 
 impl Model {
@@ -200,21 +227,85 @@ impl Model {
             Model::Grok(m) => m.provider(),
             Model::Qwen(m) => m.provider(),
             Model::Doubao(m) => m.provider(),
+            Model::Gemini(m) => m.provider(),
+        }
+    }
+
+    /// Returns the model id sent to the provider, as serialized in requests.
+    pub fn canonical_id(&self) -> &str {
+        match self {
+            Model::ChatGLM(m) => m.as_ref(),
+            Model::ChatGPT(m) => m.as_ref(),
+            Model::Claude(m) => m.as_ref(),
+            Model::Deepseek(m) => m.as_ref(),
+            Model::Grok(m) => m.as_ref(),
+            Model::Qwen(m) => m.as_ref(),
+            Model::Doubao(m) => m.as_ref(),
+            Model::Gemini(m) => m.as_ref(),
+        }
+    }
+
+    pub fn kind(&self) -> ModelKind {
+        match self {
+            Model::ChatGLM(CHATGLM::GlmEmbedding2 | CHATGLM::GlmEmbedding3) => ModelKind::Embedding,
+            Model::ChatGLM(CHATGLM::CogView4 | CHATGLM::CogView3Flash) => ModelKind::Image,
+            Model::ChatGLM(CHATGLM::CogVideoX2 | CHATGLM::CogVideoXFlash) => ModelKind::Video,
+            _ => ModelKind::Chat,
+        }
+    }
+
+    /// Returns whether the model can be used with the chat endpoint.
+    pub fn is_chat_capable(&self) -> bool {
+        self.kind() == ModelKind::Chat
+    }
+
+    /// Returns whether the model can be used with the embeddings endpoint.
+    pub fn is_embedding(&self) -> bool {
+        self.kind() == ModelKind::Embedding
+    }
+
+    /// Returns whether this is a reasoning model (o1, DeepSeek-R1,
+    /// GLM-Zero), which think before answering and reject or ignore sampling
+    /// options such as `temperature`.
+    pub fn is_reasoning(&self) -> bool {
+        matches!(
+            self,
+            Model::ChatGPT(CHATGPT::VO1Mini | CHATGPT::VO1Preview)
+                | Model::ChatGLM(CHATGLM::GlmZeroPreviewNew)
+                | Model::Deepseek(
+                    DEEPSEEK::R1Official
+                        | DEEPSEEK::R1Siliconflow
+                        | DEEPSEEK::R1Tencent
+                        | DEEPSEEK::R1Volcengine
+                        | DEEPSEEK::R1Baidu
+                        | DEEPSEEK::R1Aliyun
+                )
+        )
+    }
+
+    /// Returns whether the model accepts an Anthropic extended-thinking
+    /// budget (Claude 3.7 Sonnet).
+    pub fn supports_thinking(&self) -> bool {
+        matches!(self, Model::Claude(CLAUDE::Sonnet3_7))
+    }
+
+    pub fn family(&self) -> ModelFamily {
+        match self {
+            Model::ChatGLM(_) => ModelFamily::GLM,
+            Model::ChatGPT(_) => ModelFamily::GPT,
+            Model::Claude(_) => ModelFamily::Claude,
+            Model::Deepseek(_) => ModelFamily::Deepseek,
+            Model::Grok(_) => ModelFamily::Grok,
+            Model::Qwen(_) => ModelFamily::Qwen,
+            Model::Doubao(_) => ModelFamily::Doubao,
+            Model::Gemini(_) => ModelFamily::Gemini,
         }
     }
 }
 
 impl std::fmt::Display for Model {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Model::ChatGLM(m) => write!(f, "{}", m),
-            Model::ChatGPT(m) => write!(f, "{}", m),
-            Model::Claude(m) => write!(f, "{}", m),
-            Model::Deepseek(m) => write!(f, "{}", m),
-            Model::Grok(m) => write!(f, "{}", m),
-            Model::Qwen(m) => write!(f, "{}", m),
-            Model::Doubao(m) => write!(f, "{}", m),
-        }
+        f.write_str(self.canonical_id())
     }
 }
 
@@ -223,6 +314,36 @@ impl Serialize for Model {
     where
         S: Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        serializer.serialize_str(self.canonical_id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn family_collapses_provider_variants() {
+        assert_eq!(
+            Model::Deepseek(DEEPSEEK::R1Official).family(),
+            ModelFamily::Deepseek
+        );
+        assert_eq!(
+            Model::Deepseek(DEEPSEEK::V3Volcengine).family(),
+            ModelFamily::Deepseek
+        );
+        assert_eq!(Model::ChatGPT(CHATGPT::VO1Mini).family(), ModelFamily::GPT);
+        assert_eq!(
+            Model::Claude(CLAUDE::Sonnet3_7).family(),
+            ModelFamily::Claude
+        );
+        assert_eq!(
+            Model::ChatGLM(CHATGLM::Glm4Flash).family(),
+            ModelFamily::GLM
+        );
+        assert_eq!(
+            Model::Gemini(GEMINI::Flash2_5).family(),
+            ModelFamily::Gemini
+        );
     }
 }
@@ -0,0 +1,124 @@
+use crate::api::response::Usage;
+use crate::models::models::{CHATGLM, CHATGPT, CLAUDE, DEEPSEEK, GROK, Model};
+
+/// Published list prices of a model, in USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pricing {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+/// A cost in USD, split into its input and output parts.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Cost {
+    pub input: f64,
+    pub output: f64,
+}
+
+impl Cost {
+    pub fn total(&self) -> f64 {
+        self.input + self.output
+    }
+}
+
+impl Pricing {
+    const fn new(input_per_million: f64, output_per_million: f64) -> Self {
+        Self {
+            input_per_million,
+            output_per_million,
+        }
+    }
+
+    /// Prices the given token counts.
+    pub fn cost(&self, input_tokens: u32, output_tokens: u32) -> Cost {
+        Cost {
+            input: f64::from(input_tokens) * self.input_per_million / 1_000_000.0,
+            output: f64::from(output_tokens) * self.output_per_million / 1_000_000.0,
+        }
+    }
+
+    /// Prices a response's reported usage. Reasoning tokens are part of
+    /// `completion_tokens` and therefore bill as output.
+    pub fn cost_of_usage(&self, usage: &Usage) -> Cost {
+        self.cost(
+            usage.prompt_tokens.unwrap_or(0),
+            usage.completion_tokens.unwrap_or(0),
+        )
+    }
+}
+
+impl Model {
+    /// Returns the list price of the model on its default provider, if known.
+    pub fn pricing(&self) -> Option<Pricing> {
+        let pricing = match self {
+            Model::ChatGPT(CHATGPT::V4o) => Pricing::new(2.5, 10.0),
+            Model::ChatGPT(CHATGPT::V4oMini) => Pricing::new(0.15, 0.6),
+            Model::ChatGPT(CHATGPT::VO1Mini) => Pricing::new(1.1, 4.4),
+            Model::ChatGPT(CHATGPT::VO1Preview) => Pricing::new(15.0, 60.0),
+            Model::Claude(CLAUDE::Haiku3_5) => Pricing::new(0.8, 4.0),
+            Model::Claude(CLAUDE::Sonnet3_5) | Model::Claude(CLAUDE::Sonnet3_7) => {
+                Pricing::new(3.0, 15.0)
+            }
+            Model::Claude(CLAUDE::Opus3) => Pricing::new(15.0, 75.0),
+            Model::Deepseek(DEEPSEEK::V3Official) => Pricing::new(0.27, 1.1),
+            Model::Deepseek(DEEPSEEK::R1Official) => Pricing::new(0.55, 2.19),
+            Model::Grok(GROK::Grok2Latest) => Pricing::new(2.0, 10.0),
+            _ => return None,
+        };
+        Some(pricing)
+    }
+
+    /// Returns the maximum number of output tokens the model may generate, if known.
+    pub fn max_output_tokens(&self) -> Option<u32> {
+        match self {
+            Model::ChatGPT(CHATGPT::V4o) | Model::ChatGPT(CHATGPT::V4oMini) => Some(16_384),
+            Model::ChatGPT(CHATGPT::VO1Mini) => Some(65_536),
+            Model::ChatGPT(CHATGPT::VO1Preview) => Some(32_768),
+            Model::Claude(CLAUDE::Opus3) => Some(4_096),
+            Model::Claude(_) => Some(8_192),
+            Model::Deepseek(DEEPSEEK::V3Official) | Model::Deepseek(DEEPSEEK::R1Official) => {
+                Some(8_192)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the maximum number of tokens an embedding model accepts per
+    /// input, if known.
+    pub fn max_embedding_input_tokens(&self) -> Option<u32> {
+        match self {
+            Model::ChatGLM(CHATGLM::GlmEmbedding2) => Some(512),
+            Model::ChatGLM(CHATGLM::GlmEmbedding3) => Some(3_072),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reasoning_tokens_bill_as_output() {
+        let usage: Usage = serde_json::from_value(serde_json::json!({
+            "prompt_tokens": 1000,
+            "completion_tokens": 500,
+            "total_tokens": 1500,
+            "completion_tokens_details": { "reasoning_tokens": 300 }
+        }))
+        .unwrap();
+        assert_eq!(
+            usage
+                .completion_tokens_details
+                .as_ref()
+                .unwrap()
+                .reasoning_tokens,
+            300
+        );
+
+        let pricing = Model::ChatGPT(CHATGPT::VO1Mini).pricing().unwrap();
+        let cost = pricing.cost_of_usage(&usage);
+        assert!((cost.input - 1000.0 * 1.1 / 1_000_000.0).abs() < 1e-12);
+        assert!((cost.output - 500.0 * 4.4 / 1_000_000.0).abs() < 1e-12);
+    }
+}
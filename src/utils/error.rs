@@ -11,7 +11,11 @@ pub enum LlmHubError {
 
     /// Error originating from the underlying network client (reqwest).
     #[error("Network request failed: {0}")]
-    RequestError(#[from] reqwest::Error),
+    RequestError(#[source] reqwest::Error),
+
+    /// A request or connection attempt exceeded its configured timeout.
+    #[error("Request timed out: {0}")]
+    TimeoutError(#[source] reqwest::Error),
 
     /// Error when the API provider's rate limit is exceeded.
     #[error("Rate limit exceeded. Please wait {0} seconds before retrying.")]
@@ -25,6 +29,15 @@ pub enum LlmHubError {
     #[error("API error: {0}")]
     ApiError(String),
 
+    /// An error response whose envelope was recognized and parsed.
+    #[error("API error: {0}")]
+    ApiErrorResponse(ApiErrorDetails),
+
+    /// The response was cut off at the token limit, so its content (included
+    /// here) is incomplete. Retrying with a higher `max_tokens` may help.
+    #[error("Response truncated at the token limit: {0}")]
+    TruncatedResponse(String),
+
     /// Error related to unsupported providers, models, or API types.
     #[error("Provider or model error: {0}")]
     ProviderError(String),
@@ -40,7 +53,158 @@ pub enum LlmHubError {
     /// An I/O error occurred.
     #[error("I/O error: {0}")]
     IoError(#[from] std::io::Error),
+
+    /// Base64 data from the provider, such as an inline image, was invalid.
+    #[error("Invalid base64 data: {0}")]
+    DecodeError(#[from] base64::DecodeError),
+}
+
+impl From<reqwest::Error> for LlmHubError {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            LlmHubError::TimeoutError(error)
+        } else {
+            LlmHubError::RequestError(error)
+        }
+    }
+}
+
+/// The structured contents of a provider's error response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiErrorDetails {
+    /// The HTTP status code.
+    pub status: u16,
+    /// The provider's error code, e.g. `invalid_api_key` or `1261`.
+    pub code: Option<String>,
+    /// The error type or status name, e.g. `invalid_request_error` or `INVALID_ARGUMENT`.
+    pub kind: Option<String>,
+    pub message: String,
+}
+
+impl std::fmt::Display for ApiErrorDetails {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}", self.status)?;
+        if let Some(kind) = &self.kind {
+            write!(f, " {}", kind)?;
+        }
+        if let Some(code) = &self.code {
+            write!(f, " {}", code)?;
+        }
+        write!(f, "] {}", self.message)
+    }
+}
+
+impl ApiErrorDetails {
+    /// Parses a provider error body, recognizing:
+    /// - OpenAI-compatible, ZhipuAI and Qianfan: `{"error": {"message", "type", "code"}}`
+    /// - Anthropic: `{"type": "error", "error": {"type", "message"}}`
+    /// - Gemini: `{"error": {"code", "message", "status"}}`, optionally wrapped in an array
+    /// - Volcengine OpenAPI: `{"ResponseMetadata": {"Error": {"Code", "Message"}}}`
+    ///
+    /// Returns `None` for unrecognized shapes so callers can fall back to the raw text.
+    pub fn parse(status: u16, body: &str) -> Option<Self> {
+        let value: serde_json::Value = serde_json::from_str(body).ok()?;
+        let value = match value {
+            serde_json::Value::Array(items) => items.into_iter().next()?,
+            other => other,
+        };
+        let (error, message_key, code_key) = if let Some(error) = value.get("error") {
+            (error, "message", "code")
+        } else {
+            (
+                value.get("ResponseMetadata")?.get("Error")?,
+                "Message",
+                "Code",
+            )
+        };
+        let text = |v: &serde_json::Value| match v {
+            serde_json::Value::String(s) => Some(s.clone()),
+            serde_json::Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        };
+        let message = error.get(message_key).and_then(text)?;
+        Some(Self {
+            status,
+            code: error.get(code_key).and_then(text),
+            kind: error
+                .get("type")
+                .or_else(|| error.get("status"))
+                .and_then(text),
+            message,
+        })
+    }
+
+    /// Returns whether the error reports an exhausted quota or billing limit
+    /// (OpenAI's `insufficient_quota`), which retrying after a wait won't fix
+    /// even though it arrives as a 429.
+    pub fn is_quota_exhausted(&self) -> bool {
+        [&self.code, &self.kind]
+            .into_iter()
+            .flatten()
+            .any(|value| value == "insufficient_quota")
+    }
 }
 
 /// A specialized `Result` type for llmhub operations.
 pub type Result<T> = std::result::Result<T, LlmHubError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_gemini_error_bodies() {
+        let body = r#"[{
+            "error": {
+                "code": 400,
+                "message": "API key not valid. Please pass a valid API key.",
+                "status": "INVALID_ARGUMENT",
+                "details": [{ "@type": "type.googleapis.com/google.rpc.ErrorInfo" }]
+            }
+        }]"#;
+        let details = ApiErrorDetails::parse(400, body).unwrap();
+
+        assert_eq!(details.code.as_deref(), Some("400"));
+        assert_eq!(details.kind.as_deref(), Some("INVALID_ARGUMENT"));
+        assert_eq!(
+            details.message,
+            "API key not valid. Please pass a valid API key."
+        );
+    }
+
+    #[test]
+    fn parses_volcengine_error_bodies() {
+        let ark = r#"{"error":{"code":"InvalidParameter","message":"The parameter `model` specified in the request is not valid","param":"model","type":"BadRequest"}}"#;
+        let details = ApiErrorDetails::parse(400, ark).unwrap();
+        assert_eq!(details.code.as_deref(), Some("InvalidParameter"));
+        assert_eq!(details.kind.as_deref(), Some("BadRequest"));
+
+        let openapi = r#"{"ResponseMetadata":{"RequestId":"2024","Action":"Chat","Error":{"Code":"AccessDenied","Message":"Access denied."}}}"#;
+        let details = ApiErrorDetails::parse(403, openapi).unwrap();
+        assert_eq!(details.status, 403);
+        assert_eq!(details.code.as_deref(), Some("AccessDenied"));
+        assert_eq!(details.message, "Access denied.");
+    }
+
+    #[test]
+    fn unrecognized_bodies_fall_back_to_raw_text() {
+        assert!(ApiErrorDetails::parse(502, "<html>Bad Gateway</html>").is_none());
+        assert!(ApiErrorDetails::parse(500, r#"{"detail":"oops"}"#).is_none());
+    }
+
+    #[test]
+    fn source_returns_the_wrapped_serialization_error() {
+        use std::error::Error;
+
+        fn parse(text: &str) -> Result<u32> {
+            Ok(serde_json::from_str(text)?)
+        }
+        let inner = serde_json::from_str::<u32>("\"x\"").unwrap_err();
+        let error = parse("\"x\"").unwrap_err();
+
+        let source = error.source().expect("serialization errors have a source");
+        let source = source.downcast_ref::<serde_json::Error>().unwrap();
+        assert_eq!(source.to_string(), inner.to_string());
+        assert!(LlmHubError::ApiError("plain".into()).source().is_none());
+    }
+}
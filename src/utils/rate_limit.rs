@@ -0,0 +1,225 @@
+//! Client-side, per-provider request throttling.
+
+use crate::api::providers::ApiProvider;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+/// Returns the suggested request rate for a provider, in requests per minute,
+/// applied by [`RateLimiter::with_provider_defaults`].
+///
+/// These are deliberately conservative, roughly matching each provider's
+/// entry-level tier, so that usage rarely triggers a 429. Accounts with
+/// higher limits should override them per provider.
+pub fn default_requests_per_minute(provider: ApiProvider) -> u32 {
+    match provider {
+        ApiProvider::OpenAI => 500,
+        ApiProvider::Anthropic => 50,
+        ApiProvider::Deepseek => 60,
+        ApiProvider::Siliconflow => 300,
+        ApiProvider::Qianfan => 60,
+        ApiProvider::ZhipuAI => 60,
+        ApiProvider::ALIBAILIAN => 60,
+        ApiProvider::XAI => 60,
+        ApiProvider::Volcengine => 300,
+        ApiProvider::Tencent => 60,
+        ApiProvider::GOOGLE => 15,
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl Bucket {
+    fn new(requests_per_minute: u32) -> Self {
+        let capacity = f64::from(requests_per_minute.max(1));
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: capacity / 60.0,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Takes a token if one is available, otherwise returns how long to wait.
+    fn try_take(&mut self) -> Option<Duration> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.refill_per_sec,
+            ))
+        }
+    }
+}
+
+/// A token-bucket limiter keeping one bucket per provider.
+///
+/// Throttling is opt-in: a new limiter only throttles providers given a rate
+/// with [`RateLimiter::set_requests_per_minute`], or every provider when
+/// created with [`RateLimiter::with_provider_defaults`].
+///
+/// Timing uses `tokio::time`, so under a paused runtime
+/// (`#[tokio::test(start_paused = true)]` or `tokio::time::pause`) buckets
+/// refill only as `tokio::time::advance` moves the clock, which makes the
+/// limiter testable deterministically without real sleeps.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    unlimited: bool,
+    provider_defaults: bool,
+    overrides: Mutex<HashMap<ApiProvider, u32>>,
+    buckets: Mutex<HashMap<ApiProvider, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a limiter throttling each provider to
+    /// [`default_requests_per_minute`] unless overridden.
+    pub fn with_provider_defaults() -> Self {
+        Self {
+            provider_defaults: true,
+            ..Self::default()
+        }
+    }
+
+    /// Creates a limiter that never throttles, even providers given a rate.
+    ///
+    /// Requests are then only paced by the caller, so bursts may be rejected
+    /// by the provider with `429 Too Many Requests`.
+    pub fn unlimited() -> Self {
+        Self {
+            unlimited: true,
+            ..Self::default()
+        }
+    }
+
+    /// Returns a limiter with the same per-provider limits but empty buckets.
+    pub fn with_same_limits(&self) -> Self {
+        let overrides = self
+            .overrides
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        Self {
+            unlimited: self.unlimited,
+            provider_defaults: self.provider_defaults,
+            overrides: Mutex::new(overrides),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Throttles a provider to `requests_per_minute`, replacing any default.
+    pub fn set_requests_per_minute(&self, provider: ApiProvider, requests_per_minute: u32) {
+        self.overrides
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(provider, requests_per_minute);
+        self.buckets
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&provider);
+    }
+
+    /// Returns the effective requests-per-minute for a provider, or `None`
+    /// when requests to it aren't throttled.
+    pub fn requests_per_minute(&self, provider: ApiProvider) -> Option<u32> {
+        if self.unlimited {
+            return None;
+        }
+        let rate = self
+            .overrides
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&provider)
+            .copied();
+        rate.or_else(|| {
+            self.provider_defaults
+                .then(|| default_requests_per_minute(provider))
+        })
+    }
+
+    /// Waits until a request to `provider` may be sent.
+    pub async fn acquire(&self, provider: ApiProvider) {
+        loop {
+            let Some(requests_per_minute) = self.requests_per_minute(provider) else {
+                return;
+            };
+            let wait = {
+                let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+                buckets
+                    .entry(provider)
+                    .or_insert_with(|| Bucket::new(requests_per_minute))
+                    .try_take()
+            };
+            match wait {
+                None => return,
+                Some(wait) => {
+                    log::debug!("Throttling request to {} for {:?}", provider, wait);
+                    tokio::time::sleep(wait).await;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn does_not_throttle_unless_configured() {
+        let limiter = RateLimiter::new();
+        let start = Instant::now();
+        for _ in 0..1000 {
+            limiter.acquire(ApiProvider::GOOGLE).await;
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        assert_eq!(limiter.requests_per_minute(ApiProvider::GOOGLE), None);
+
+        let defaults = RateLimiter::with_provider_defaults();
+        assert_eq!(defaults.requests_per_minute(ApiProvider::GOOGLE), Some(15));
+        defaults.set_requests_per_minute(ApiProvider::GOOGLE, 100);
+        assert_eq!(defaults.requests_per_minute(ApiProvider::GOOGLE), Some(100));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn unlimited_ignores_configured_rates() {
+        let limiter = RateLimiter::unlimited();
+        limiter.set_requests_per_minute(ApiProvider::OpenAI, 1);
+        let start = Instant::now();
+        for _ in 0..100 {
+            limiter.acquire(ApiProvider::OpenAI).await;
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn acquire_waits_for_the_bucket_to_refill() {
+        let limiter = RateLimiter::new();
+        limiter.set_requests_per_minute(ApiProvider::OpenAI, 60);
+        for _ in 0..60 {
+            limiter.acquire(ApiProvider::OpenAI).await;
+        }
+
+        let mut next = std::pin::pin!(limiter.acquire(ApiProvider::OpenAI));
+        assert!(futures::poll!(next.as_mut()).is_pending());
+        tokio::time::advance(Duration::from_millis(500)).await;
+        assert!(futures::poll!(next.as_mut()).is_pending());
+        tokio::time::advance(Duration::from_millis(500)).await;
+        assert!(futures::poll!(next.as_mut()).is_ready());
+    }
+}
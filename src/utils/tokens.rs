@@ -0,0 +1,205 @@
+//! Pluggable tokenizers used for context budgeting.
+
+use crate::api::message::Message;
+use async_trait::async_trait;
+
+/// Tokens added per message for role and framing overhead.
+pub const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+
+/// Counts the tokens a piece of text or a conversation occupies.
+pub trait Tokenizer: Send + Sync {
+    /// Returns the token count of `text`.
+    fn count(&self, text: &str) -> usize;
+
+    /// Returns the token count of a conversation, including per-message overhead.
+    fn count_messages(&self, messages: &[Message]) -> usize {
+        messages
+            .iter()
+            .map(|m| MESSAGE_OVERHEAD_TOKENS + m.text().map_or(0, |c| self.count(&c)))
+            .sum()
+    }
+}
+
+/// A tokenizer whose counts may need I/O, such as a provider's
+/// token-counting endpoint.
+///
+/// Every [`Tokenizer`] is also an `AsyncTokenizer`, so the async budgeting
+/// paths accept either kind.
+#[async_trait]
+pub trait AsyncTokenizer: Send + Sync {
+    /// Returns the token count of `text`.
+    async fn count(&self, text: &str) -> usize;
+
+    /// Returns the token count of a conversation, including per-message overhead.
+    async fn count_messages(&self, messages: &[Message]) -> usize {
+        let mut total = 0;
+        for message in messages {
+            total += MESSAGE_OVERHEAD_TOKENS;
+            if let Some(text) = message.text() {
+                total += self.count(&text).await;
+            }
+        }
+        total
+    }
+}
+
+#[async_trait]
+impl<T: Tokenizer + ?Sized> AsyncTokenizer for T {
+    async fn count(&self, text: &str) -> usize {
+        Tokenizer::count(self, text)
+    }
+
+    async fn count_messages(&self, messages: &[Message]) -> usize {
+        Tokenizer::count_messages(self, messages)
+    }
+}
+
+/// A model-agnostic estimate of roughly four characters per token.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicTokenizer;
+
+impl Tokenizer for HeuristicTokenizer {
+    fn count(&self, text: &str) -> usize {
+        text.chars().count().div_ceil(4)
+    }
+}
+
+/// An exact BPE tokenizer for OpenAI models, backed by `tiktoken-rs`.
+#[cfg(feature = "tiktoken")]
+pub struct TiktokenTokenizer {
+    bpe: tiktoken_rs::CoreBPE,
+}
+
+#[cfg(feature = "tiktoken")]
+impl TiktokenTokenizer {
+    /// Loads the encoding used by the given OpenAI model id, e.g. `gpt-4o`.
+    pub fn for_model(model: &str) -> crate::Result<Self> {
+        tiktoken_rs::get_bpe_from_model(model)
+            .map(|bpe| Self { bpe })
+            .map_err(|e| crate::LlmHubError::ConfigError(e.to_string()))
+    }
+}
+
+#[cfg(feature = "tiktoken")]
+impl Tokenizer for TiktokenTokenizer {
+    fn count(&self, text: &str) -> usize {
+        self.bpe.encode_with_special_tokens(text).len()
+    }
+}
+
+/// Splits `text` into chunks of at most `max_tokens` tokens each, with
+/// consecutive chunks sharing up to `overlap` tokens of context.
+///
+/// Chunks break at whitespace; a single word longer than `max_tokens` is
+/// cut at character boundaries so that no chunk exceeds the limit.
+pub fn chunk_text(
+    text: &str,
+    max_tokens: usize,
+    overlap: usize,
+    tokenizer: &dyn Tokenizer,
+) -> Vec<String> {
+    let words: Vec<&str> = text
+        .split_inclusive(char::is_whitespace)
+        .flat_map(|word| split_word(word, max_tokens, tokenizer))
+        .collect();
+    let counts: Vec<usize> = words.iter().map(|w| tokenizer.count(w)).collect();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+
+    while start < words.len() {
+        let mut end = start;
+        let mut total = 0;
+        while end < words.len() && (end == start || total + counts[end] <= max_tokens) {
+            total += counts[end];
+            end += 1;
+        }
+        chunks.push(words[start..end].concat());
+        if end == words.len() {
+            break;
+        }
+
+        let mut next = end;
+        let mut shared = 0;
+        while next > start + 1 && shared + counts[next - 1] <= overlap {
+            shared += counts[next - 1];
+            next -= 1;
+        }
+        start = next;
+    }
+
+    chunks
+}
+
+/// Cuts `word` into the longest prefixes that fit in `max_tokens`, always
+/// taking at least one character so that progress is made.
+fn split_word<'a>(mut word: &'a str, max_tokens: usize, tokenizer: &dyn Tokenizer) -> Vec<&'a str> {
+    let mut pieces = Vec::new();
+    while tokenizer.count(word) > max_tokens {
+        let ends: Vec<usize> = word.char_indices().skip(1).map(|(i, _)| i).collect();
+        let fits = ends.partition_point(|&end| tokenizer.count(&word[..end]) <= max_tokens);
+        let end = if fits == 0 {
+            ends.first().copied().unwrap_or(word.len())
+        } else {
+            ends[fits - 1]
+        };
+        pieces.push(&word[..end]);
+        word = &word[end..];
+    }
+    if !word.is_empty() {
+        pieces.push(word);
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Counts one token per whitespace-separated word.
+    struct WordTokenizer;
+
+    impl Tokenizer for WordTokenizer {
+        fn count(&self, text: &str) -> usize {
+            text.split_whitespace().count()
+        }
+    }
+
+    #[test]
+    fn chunk_text_fills_chunks_to_the_exact_boundary() {
+        let text = "a b c d e f";
+        assert_eq!(chunk_text(text, 3, 0, &WordTokenizer), ["a b c ", "d e f"]);
+        assert_eq!(chunk_text(text, 6, 0, &WordTokenizer), [text]);
+    }
+
+    #[test]
+    fn chunk_text_overlaps_consecutive_chunks() {
+        assert_eq!(
+            chunk_text("a b c d e f", 3, 1, &WordTokenizer),
+            ["a b c ", "c d e ", "e f"]
+        );
+    }
+
+    #[tokio::test]
+    async fn tokenizers_count_asynchronously() {
+        let messages = [Message::user("abcdefgh")];
+        let tokenizer: &dyn AsyncTokenizer = &HeuristicTokenizer;
+        assert_eq!(tokenizer.count("abcd").await, 1);
+        assert_eq!(
+            tokenizer.count_messages(&messages).await,
+            MESSAGE_OVERHEAD_TOKENS + 2
+        );
+    }
+
+    #[test]
+    fn chunk_text_cuts_words_longer_than_the_limit() {
+        assert_eq!(
+            chunk_text("abcdefghij", 2, 0, &HeuristicTokenizer),
+            ["abcdefgh", "ij"]
+        );
+        assert!(
+            chunk_text("ab abcdefghijklmnopq cd", 2, 0, &HeuristicTokenizer)
+                .iter()
+                .all(|chunk| Tokenizer::count(&HeuristicTokenizer, chunk) <= 2)
+        );
+    }
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use common::{MockServer, Reply, temp_path};
+use llmhub::api::audio::TranscriptionRequest;
+use llmhub::api::providers::ApiProvider;
+use serde_json::json;
+
+#[tokio::test]
+async fn transcribes_a_file_from_disk_as_multipart() {
+    let server = MockServer::start(|_| Reply::json(json!({ "text": "hello there" }))).await;
+    let path = temp_path("clip \"1\".wav");
+    tokio::fs::write(&path, b"RIFF-fake-audio").await.unwrap();
+
+    let request = TranscriptionRequest::from_path(ApiProvider::OpenAI, "whisper-1", &path)
+        .with_language("en");
+    let response = server
+        .client(ApiProvider::OpenAI)
+        .transcribe(&request)
+        .await
+        .unwrap();
+    tokio::fs::remove_file(&path).await.unwrap();
+
+    assert_eq!(response.text, "hello there");
+    let sent = server.last_request();
+    assert_eq!(sent.method, "POST");
+    assert_eq!(sent.path, "/audio/transcriptions");
+    let content_type = sent.header("content-type").unwrap();
+    let boundary = content_type
+        .strip_prefix("multipart/form-data; boundary=")
+        .unwrap()
+        .to_string();
+    let length: usize = sent.header("content-length").unwrap().parse().unwrap();
+    assert_eq!(length, sent.body.len());
+
+    let body = String::from_utf8(sent.body).unwrap();
+    assert!(body.contains("name=\"model\"\r\n\r\nwhisper-1\r\n"));
+    assert!(body.contains("name=\"language\"\r\n\r\nen\r\n"));
+    assert!(body.contains("%221%22.wav\"\r\n"));
+    assert!(body.contains("\r\n\r\nRIFF-fake-audio\r\n"));
+    assert!(body.ends_with(&format!("--{}--\r\n", boundary)));
+}
+
+#[tokio::test]
+async fn transcribes_from_an_async_reader() {
+    let server = MockServer::start(|_| Reply::json(json!({ "text": "ok" }))).await;
+    let audio: &'static [u8] = b"streamed-audio";
+    let request = TranscriptionRequest::from_reader(
+        ApiProvider::OpenAI,
+        "whisper-1",
+        audio,
+        audio.len() as u64,
+        "clip.mp3",
+    );
+    server
+        .client(ApiProvider::OpenAI)
+        .transcribe(&request)
+        .await
+        .unwrap();
+
+    let body = String::from_utf8(server.last_request().body).unwrap();
+    assert!(body.contains("filename=\"clip.mp3\""));
+    assert!(body.contains("\r\n\r\nstreamed-audio\r\n"));
+}
@@ -0,0 +1,244 @@
+mod common;
+
+use common::{MockServer, Reply, chat_completion, content_chunk};
+use futures::StreamExt;
+use llmhub::api::client::{OverlongPolicy, TRUNCATION_MARKER};
+use llmhub::api::message::Message;
+use llmhub::api::providers::ApiProvider;
+use llmhub::api::request::{ApiRequest, RequestOptions};
+use llmhub::api::session::Session;
+use llmhub::models::models::{CHATGLM, CHATGPT, Model};
+use llmhub::utils::error::LlmHubError;
+use serde_json::json;
+
+fn request() -> ApiRequest {
+    ApiRequest::new(Model::ChatGPT(CHATGPT::V4oMini), None).add_message(Message::user("hi"))
+}
+
+#[tokio::test]
+async fn body_stream_flag_matches_the_method() {
+    let server = MockServer::start(|recorded| {
+        if recorded.json()["stream"] == json!(true) {
+            Reply::sse(&[&content_chunk("hi"), "[DONE]"])
+        } else {
+            Reply::json(chat_completion("hi"))
+        }
+    })
+    .await;
+    let client = server.client(ApiProvider::OpenAI);
+
+    client.chat(&request().stream(true)).await.unwrap();
+    assert_eq!(server.last_request().json()["stream"], json!(false));
+
+    let chunks: Vec<_> = client
+        .chat_stream(&request().stream(false))
+        .unwrap()
+        .collect()
+        .await;
+    assert!(chunks.iter().all(Result::is_ok));
+    assert_eq!(server.last_request().json()["stream"], json!(true));
+}
+
+#[tokio::test]
+async fn chat_routed_picks_the_model_from_the_messages() {
+    let server = MockServer::json(chat_completion("ok")).await;
+    let client = server.client(ApiProvider::OpenAI);
+    let select = |messages: &[Message]| {
+        if messages.len() > 2 {
+            Model::ChatGPT(CHATGPT::V4o)
+        } else {
+            Model::ChatGPT(CHATGPT::V4oMini)
+        }
+    };
+    let mut session = Session::new();
+    session.add_message(Message::user("short"));
+
+    client
+        .chat_routed(select, &session, RequestOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(server.last_request().json()["model"], "gpt-4o-mini");
+
+    session.add_message(Message::assistant("reply"));
+    session.add_message(Message::user("follow-up"));
+    client
+        .chat_routed(select, &session, RequestOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(server.last_request().json()["model"], "gpt-4o");
+}
+
+#[derive(Debug, PartialEq, serde::Deserialize)]
+struct City {
+    name: String,
+    population: u64,
+}
+
+#[tokio::test]
+async fn chat_structured_parses_the_reply_into_the_target_type() {
+    let server = MockServer::json(chat_completion(
+        r#"{"name": "Paris", "population": 2102650}"#,
+    ))
+    .await;
+    let city: City = server
+        .client(ApiProvider::OpenAI)
+        .chat_structured(
+            Model::ChatGPT(CHATGPT::V4oMini),
+            vec![Message::user("Describe Paris as JSON")],
+            RequestOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        city,
+        City {
+            name: "Paris".to_string(),
+            population: 2_102_650,
+        }
+    );
+    assert_eq!(
+        server.last_request().json()["response_format"],
+        json!({ "type": "json_object" })
+    );
+}
+
+#[tokio::test]
+async fn chat_structured_rejects_content_not_matching_the_type() {
+    let server = MockServer::json(chat_completion(r#"{"name": "Paris"}"#)).await;
+    let result: Result<City, _> = server
+        .client(ApiProvider::OpenAI)
+        .chat_structured(
+            Model::ChatGPT(CHATGPT::V4oMini),
+            vec![Message::user("Describe Paris as JSON")],
+            RequestOptions::default(),
+        )
+        .await;
+
+    assert!(matches!(result, Err(LlmHubError::SerializationError(_))));
+}
+
+#[tokio::test]
+async fn overlong_messages_are_truncated_or_rejected_per_policy() {
+    let server = MockServer::json(chat_completion("ok")).await;
+    let overlong = ApiRequest::new(Model::ChatGPT(CHATGPT::V4oMini), None)
+        .add_message(Message::user("abcdefghij"));
+
+    server
+        .client(ApiProvider::OpenAI)
+        .with_max_message_chars(4, OverlongPolicy::Truncate)
+        .chat(&overlong)
+        .await
+        .unwrap();
+    assert_eq!(
+        server.last_request().json()["messages"][0]["content"],
+        format!("abcd{}", TRUNCATION_MARKER)
+    );
+
+    let result = server
+        .client(ApiProvider::OpenAI)
+        .with_max_message_chars(4, OverlongPolicy::Reject)
+        .chat(&overlong)
+        .await;
+    assert!(matches!(result, Err(LlmHubError::ConfigError(_))));
+    assert_eq!(server.requests().len(), 1);
+}
+
+fn guided_request() -> ApiRequest {
+    request().with_options(RequestOptions {
+        guided_json: Some(json!({ "type": "object" })),
+        guided_regex: Some("[a-z]+".to_string()),
+        guided_grammar: Some("root ::= \"yes\" | \"no\"".to_string()),
+        ..Default::default()
+    })
+}
+
+#[tokio::test]
+async fn guided_decoding_is_sent_only_to_enabled_providers() {
+    let server = MockServer::json(chat_completion("yes")).await;
+
+    server
+        .client(ApiProvider::OpenAI)
+        .with_guided_decoding(ApiProvider::OpenAI)
+        .chat(&guided_request())
+        .await
+        .unwrap();
+    let body = server.last_request().json();
+    assert_eq!(body["guided_json"], json!({ "type": "object" }));
+    assert_eq!(body["guided_regex"], "[a-z]+");
+    assert_eq!(body["guided_grammar"], "root ::= \"yes\" | \"no\"");
+
+    server
+        .client(ApiProvider::OpenAI)
+        .chat(&guided_request())
+        .await
+        .unwrap();
+    let body = server.last_request().json();
+    for field in ["guided_json", "guided_regex", "guided_grammar"] {
+        assert!(body.get(field).is_none(), "{} was sent", field);
+    }
+
+    let result = server
+        .client(ApiProvider::OpenAI)
+        .with_strict_capabilities(true)
+        .chat(&guided_request())
+        .await;
+    assert!(matches!(result, Err(LlmHubError::ProviderError(_))));
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn conversation_carries_history_across_turns() {
+    let server = MockServer::start(|recorded| {
+        let turns = recorded.json()["messages"].as_array().unwrap().len();
+        if recorded.json()["stream"] == json!(true) {
+            Reply::sse(&[&content_chunk("third"), "[DONE]"])
+        } else {
+            Reply::json(chat_completion(if turns == 1 { "first" } else { "second" }))
+        }
+    })
+    .await;
+    let mut conversation = server
+        .client(ApiProvider::OpenAI)
+        .conversation(Model::ChatGPT(CHATGPT::V4oMini), RequestOptions::default());
+
+    assert_eq!(conversation.send("one").await.unwrap(), "first");
+    assert_eq!(conversation.send("two").await.unwrap(), "second");
+    let sent = server.last_request().json();
+    let roles: Vec<_> = sent["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| (m["role"].as_str().unwrap(), m["content"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        roles,
+        [("user", "one"), ("assistant", "first"), ("user", "two")]
+    );
+
+    let deltas: Vec<String> = conversation
+        .send_stream("three")
+        .unwrap()
+        .map(|delta| delta.unwrap())
+        .collect()
+        .await;
+    assert_eq!(deltas, ["third"]);
+    let history: Vec<_> = conversation
+        .session()
+        .iter()
+        .map(|m| m.text().unwrap_or_default().into_owned())
+        .collect();
+    assert_eq!(history, ["one", "first", "two", "second", "three", "third"]);
+}
+
+#[tokio::test]
+async fn chat_rejects_embedding_models_without_sending() {
+    let server = MockServer::json(chat_completion("ok")).await;
+    let request = ApiRequest::new(Model::ChatGLM(CHATGLM::GlmEmbedding3), None)
+        .add_message(Message::user("hi"));
+
+    let result = server.client(ApiProvider::ZhipuAI).chat(&request).await;
+
+    assert!(matches!(result, Err(LlmHubError::ProviderError(_))));
+    assert!(server.requests().is_empty());
+}
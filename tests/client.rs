@@ -0,0 +1,33 @@
+use async_trait::async_trait;
+use llmhub::api::client::Client;
+use llmhub::api::message::Message;
+use llmhub::models::models::{CHATGPT, Model};
+use llmhub::utils::tokens::{AsyncTokenizer, MESSAGE_OVERHEAD_TOKENS};
+use std::sync::Arc;
+
+struct RemoteTokenizer;
+
+#[async_trait]
+impl AsyncTokenizer for RemoteTokenizer {
+    async fn count(&self, text: &str) -> usize {
+        tokio::task::yield_now().await;
+        text.len()
+    }
+}
+
+#[tokio::test]
+async fn count_tokens_prefers_a_registered_async_tokenizer() {
+    let model = Model::ChatGPT(CHATGPT::V4o);
+    let other = Model::ChatGPT(CHATGPT::V4oMini);
+    let client = Client::new("key".into()).with_async_tokenizer(&model, Arc::new(RemoteTokenizer));
+    let messages = [Message::user("abcdefgh")];
+
+    assert_eq!(
+        client.count_tokens(&model, &messages).await,
+        MESSAGE_OVERHEAD_TOKENS + 8
+    );
+    assert_eq!(
+        client.count_tokens(&other, &messages).await,
+        MESSAGE_OVERHEAD_TOKENS + 2
+    );
+}
@@ -0,0 +1,346 @@
+//! A minimal HTTP/1.1 server for exercising the client against canned
+//! replies without touching the network.
+
+#![allow(dead_code)]
+
+use llmhub::api::client::Client;
+use llmhub::api::providers::ApiProvider;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// A request as received by the server.
+#[derive(Debug, Clone)]
+pub struct Recorded {
+    pub method: String,
+    pub path: String,
+    /// Header names are lowercased.
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl Recorded {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_slice(&self.body).expect("request body is JSON")
+    }
+}
+
+/// A canned reply.
+pub enum Reply {
+    /// A complete response with the given status, headers and body.
+    Full {
+        status: u16,
+        headers: Vec<(String, String)>,
+        body: Vec<u8>,
+    },
+    /// A chunked `text/event-stream` response sending each chunk in turn,
+    /// then either finishing or holding the connection open until the
+    /// client goes away.
+    Stream { chunks: Vec<String>, hang: bool },
+}
+
+impl Reply {
+    pub fn json(body: serde_json::Value) -> Self {
+        Self::status(200, body)
+    }
+
+    pub fn status(status: u16, body: serde_json::Value) -> Self {
+        Self::Full {
+            status,
+            headers: vec![("content-type".into(), "application/json".into())],
+            body: body.to_string().into_bytes(),
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        if let Self::Full { headers, .. } = &mut self {
+            headers.push((name.into(), value.into()));
+        }
+        self
+    }
+
+    pub fn sse(events: &[&str]) -> Self {
+        Self::Stream {
+            chunks: events.iter().map(|e| format!("data: {}\n\n", e)).collect(),
+            hang: false,
+        }
+    }
+
+    pub fn raw_stream(chunks: &[&str]) -> Self {
+        Self::Stream {
+            chunks: chunks.iter().map(|c| c.to_string()).collect(),
+            hang: false,
+        }
+    }
+
+    /// Sends `chunks`, then never finishes the body.
+    pub fn hanging_stream(chunks: &[&str]) -> Self {
+        Self::Stream {
+            chunks: chunks.iter().map(|c| c.to_string()).collect(),
+            hang: true,
+        }
+    }
+}
+
+type Handler = dyn Fn(&Recorded) -> Reply + Send + Sync;
+
+pub struct MockServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<Recorded>>>,
+    disconnected: Arc<AtomicBool>,
+}
+
+impl MockServer {
+    /// Starts a server answering every request with `handler`.
+    pub async fn start(handler: impl Fn(&Recorded) -> Reply + Send + Sync + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let disconnected = Arc::new(AtomicBool::new(false));
+        let handler: Arc<Handler> = Arc::new(handler);
+        {
+            let requests = requests.clone();
+            let disconnected = disconnected.clone();
+            tokio::spawn(async move {
+                while let Ok((socket, _)) = listener.accept().await {
+                    tokio::spawn(serve(
+                        socket,
+                        handler.clone(),
+                        requests.clone(),
+                        disconnected.clone(),
+                    ));
+                }
+            });
+        }
+        Self {
+            url,
+            requests,
+            disconnected,
+        }
+    }
+
+    /// Starts a server answering every request with the same JSON body.
+    pub async fn json(body: serde_json::Value) -> Self {
+        Self::start(move |_| Reply::json(body.clone())).await
+    }
+
+    /// A client whose `provider` endpoints point at this server.
+    pub fn client(&self, provider: ApiProvider) -> Client {
+        Client::new("test-key".into())
+            .without_rate_limit()
+            .with_base_url(provider, self.url.clone())
+    }
+
+    pub fn requests(&self) -> Vec<Recorded> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn last_request(&self) -> Recorded {
+        self.requests().pop().expect("a request was received")
+    }
+
+    /// Waits up to `timeout` for a hanging stream's client to disconnect.
+    pub async fn wait_for_disconnect(&self, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        while tokio::time::Instant::now() < deadline {
+            if self.disconnected.load(Ordering::SeqCst) {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        self.disconnected.load(Ordering::SeqCst)
+    }
+}
+
+async fn serve(
+    mut socket: TcpStream,
+    handler: Arc<Handler>,
+    requests: Arc<Mutex<Vec<Recorded>>>,
+    disconnected: Arc<AtomicBool>,
+) {
+    let mut buffer = Vec::new();
+    while let Some(request) = read_request(&mut socket, &mut buffer).await {
+        requests.lock().unwrap().push(request.clone());
+        match handler(&request) {
+            Reply::Full {
+                status,
+                headers,
+                body,
+            } => {
+                let mut head = format!(
+                    "HTTP/1.1 {} Mock\r\ncontent-length: {}\r\n",
+                    status,
+                    body.len()
+                );
+                for (name, value) in headers {
+                    head.push_str(&format!("{}: {}\r\n", name, value));
+                }
+                head.push_str("\r\n");
+                if socket.write_all(head.as_bytes()).await.is_err()
+                    || socket.write_all(&body).await.is_err()
+                {
+                    return;
+                }
+            }
+            Reply::Stream { chunks, hang } => {
+                let head = "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\
+                            transfer-encoding: chunked\r\n\r\n";
+                if socket.write_all(head.as_bytes()).await.is_err() {
+                    return;
+                }
+                for chunk in chunks {
+                    let frame = format!("{:x}\r\n{}\r\n", chunk.len(), chunk);
+                    if socket.write_all(frame.as_bytes()).await.is_err() {
+                        disconnected.store(true, Ordering::SeqCst);
+                        return;
+                    }
+                    let _ = socket.flush().await;
+                }
+                if hang {
+                    // Nothing more is sent; a read returning EOF (or failing)
+                    // means the client has torn the connection down.
+                    let mut scratch = [0u8; 1024];
+                    loop {
+                        match socket.read(&mut scratch).await {
+                            Ok(0) | Err(_) => {
+                                disconnected.store(true, Ordering::SeqCst);
+                                return;
+                            }
+                            Ok(_) => continue,
+                        }
+                    }
+                }
+                if socket.write_all(b"0\r\n\r\n").await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+async fn read_request(socket: &mut TcpStream, buffer: &mut Vec<u8>) -> Option<Recorded> {
+    let head_end = loop {
+        if let Some(pos) = find(buffer, b"\r\n\r\n") {
+            break pos;
+        }
+        fill(socket, buffer).await?;
+    };
+    let head = String::from_utf8_lossy(&buffer[..head_end]).into_owned();
+    buffer.drain(..head_end + 4);
+
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split(' ');
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let body = if let Some(length) = headers.get("content-length") {
+        let length: usize = length.parse().ok()?;
+        while buffer.len() < length {
+            fill(socket, buffer).await?;
+        }
+        buffer.drain(..length).collect()
+    } else if headers
+        .get("transfer-encoding")
+        .is_some_and(|te| te.eq_ignore_ascii_case("chunked"))
+    {
+        read_chunked(socket, buffer).await?
+    } else {
+        Vec::new()
+    };
+
+    Some(Recorded {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+async fn read_chunked(socket: &mut TcpStream, buffer: &mut Vec<u8>) -> Option<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let line_end = loop {
+            if let Some(pos) = find(buffer, b"\r\n") {
+                break pos;
+            }
+            fill(socket, buffer).await?;
+        };
+        let size_line = String::from_utf8_lossy(&buffer[..line_end]).into_owned();
+        let size = usize::from_str_radix(size_line.split(';').next()?.trim(), 16).ok()?;
+        buffer.drain(..line_end + 2);
+        while buffer.len() < size + 2 {
+            fill(socket, buffer).await?;
+        }
+        body.extend(buffer.drain(..size));
+        buffer.drain(..2);
+        if size == 0 {
+            return Some(body);
+        }
+    }
+}
+
+async fn fill<R: AsyncRead + Unpin>(socket: &mut R, buffer: &mut Vec<u8>) -> Option<()> {
+    let mut chunk = [0u8; 8192];
+    match socket.read(&mut chunk).await {
+        Ok(0) | Err(_) => None,
+        Ok(n) => {
+            buffer.extend_from_slice(&chunk[..n]);
+            Some(())
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// A minimal OpenAI-style chat completion carrying `content`.
+pub fn chat_completion(content: &str) -> serde_json::Value {
+    serde_json::json!({
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "created": 1,
+        "model": "gpt-4o-mini",
+        "choices": [{
+            "index": 0,
+            "message": { "role": "assistant", "content": content },
+            "finish_reason": "stop"
+        }],
+        "usage": { "prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2 }
+    })
+}
+
+/// A unique path under the system temp directory.
+pub fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("llmhub-{}-{}", uuid::Uuid::new_v4().simple(), name))
+}
+
+/// An OpenAI-style stream chunk with the given delta, as JSON text.
+pub fn stream_chunk(delta: serde_json::Value, finish_reason: Option<&str>) -> String {
+    serde_json::json!({
+        "id": "chatcmpl-1",
+        "object": "chat.completion.chunk",
+        "created": 1,
+        "model": "gpt-4o-mini",
+        "choices": [{ "index": 0, "delta": delta, "finish_reason": finish_reason }]
+    })
+    .to_string()
+}
+
+/// A stream chunk carrying a piece of assistant text.
+pub fn content_chunk(content: &str) -> String {
+    stream_chunk(serde_json::json!({ "content": content }), None)
+}
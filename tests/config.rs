@@ -0,0 +1,41 @@
+mod common;
+
+use common::{MockServer, chat_completion, temp_path};
+use llmhub::api::config::ProviderConfig;
+use llmhub::api::message::Message;
+use llmhub::api::providers::ApiProvider;
+use llmhub::api::request::ApiRequest;
+use llmhub::models::models::{DEEPSEEK, Model};
+use serde_json::json;
+
+#[tokio::test]
+async fn to_client_uses_a_loaded_config() {
+    let server = MockServer::json(chat_completion("hi")).await;
+    let path = temp_path("llmhub.json");
+    let configs = json!([
+        {
+            "api_provider": "deepseek",
+            "api_base_url": server.url,
+            "api_key": "sk-from-file",
+            "extra_headers": { "x-team": "research" }
+        },
+        { "api_provider": "tencent", "api_base_url": null, "api_key": null }
+    ]);
+    std::fs::write(&path, configs.to_string()).unwrap();
+
+    let loaded = ProviderConfig::load_from_file(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let config = ProviderConfig::get_provider_config(&loaded, ApiProvider::Deepseek).unwrap();
+    let client = config.to_client().unwrap();
+    let request = ApiRequest::new(Model::Deepseek(DEEPSEEK::V3Official), None)
+        .add_message(Message::user("hi"));
+    client.chat(&request).await.unwrap();
+
+    let sent = server.last_request();
+    assert_eq!(sent.path, "/chat/completions");
+    assert_eq!(sent.header("authorization"), Some("Bearer sk-from-file"));
+    assert_eq!(sent.header("x-team"), Some("research"));
+
+    let keyless = ProviderConfig::get_provider_config(&loaded, ApiProvider::Tencent).unwrap();
+    assert!(keyless.to_client().is_err());
+}
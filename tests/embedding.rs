@@ -0,0 +1,154 @@
+mod common;
+
+use common::{MockServer, Reply};
+use futures::StreamExt;
+use llmhub::api::embedding::{EmbeddingRequest, OversizePolicy};
+use llmhub::api::providers::ApiProvider;
+use llmhub::models::models::{CHATGLM, Model};
+use llmhub::utils::error::LlmHubError;
+use llmhub::utils::tokens::{HeuristicTokenizer, Tokenizer};
+use serde_json::json;
+
+/// Answers each input `doc-<n>` with the one-dimensional embedding `[n]`.
+fn embed_reply(recorded: &common::Recorded) -> Reply {
+    let body = recorded.json();
+    let data: Vec<_> = body["input"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .enumerate()
+        .map(|(index, input)| {
+            let n: f32 = input.as_str().unwrap()["doc-".len()..].parse().unwrap();
+            json!({ "index": index, "embedding": [n] })
+        })
+        .collect();
+    Reply::json(json!({ "data": data, "model": "embedding-3" }))
+}
+
+#[tokio::test]
+async fn embed_stream_batches_and_keeps_original_indices() {
+    let server = MockServer::start(embed_reply).await;
+    let inputs = futures::stream::iter((0..50).map(|i| format!("doc-{}", i)));
+
+    let mut results: Vec<(usize, Vec<f32>)> = server
+        .client(ApiProvider::ZhipuAI)
+        .embed_stream(Model::ChatGLM(CHATGLM::GlmEmbedding3), inputs, 8)
+        .map(|result| result.unwrap())
+        .collect()
+        .await;
+    results.sort_by_key(|(index, _)| *index);
+
+    assert_eq!(results.len(), 50);
+    for (i, (index, embedding)) in results.iter().enumerate() {
+        assert_eq!(*index, i);
+        assert_eq!(embedding, &[i as f32]);
+    }
+    let batch_sizes: Vec<usize> = server
+        .requests()
+        .iter()
+        .map(|r| r.json()["input"].as_array().unwrap().len())
+        .collect();
+    assert_eq!(batch_sizes.len(), 7);
+    assert!(batch_sizes.iter().all(|&size| size <= 8));
+    assert_eq!(batch_sizes.iter().sum::<usize>(), 50);
+}
+
+/// Answers input `n` of each request with `[1, 0]` when `n` is even and
+/// `[0, 1]` when it is odd.
+fn alternating_reply(recorded: &common::Recorded) -> Reply {
+    let count = recorded.json()["input"].as_array().unwrap().len();
+    let data: Vec<_> = (0..count)
+        .map(|index| {
+            let embedding = if index % 2 == 0 {
+                [1.0, 0.0]
+            } else {
+                [0.0, 1.0]
+            };
+            json!({ "index": index, "embedding": embedding })
+        })
+        .collect();
+    Reply::json(json!({ "data": data, "model": "embedding-3" }))
+}
+
+/// An input well over the 3,072-token limit of `embedding-3`, ending in a
+/// single word that is itself over the limit.
+fn oversized_input() -> String {
+    format!("{}{}", "word ".repeat(2_000), "x".repeat(20_000))
+}
+
+fn oversized_request(policy: OversizePolicy) -> EmbeddingRequest {
+    EmbeddingRequest::new(
+        Model::ChatGLM(CHATGLM::GlmEmbedding3),
+        vec!["short".to_string(), oversized_input()],
+    )
+    .with_oversize_policy(policy)
+}
+
+#[tokio::test]
+async fn split_policy_sends_chunks_within_the_limit_and_normalizes() {
+    let server = MockServer::start(alternating_reply).await;
+    let response = server
+        .client(ApiProvider::ZhipuAI)
+        .embeddings(&oversized_request(OversizePolicy::Split))
+        .await
+        .unwrap();
+
+    let sent = server.last_request().json();
+    let sent = sent["input"].as_array().unwrap();
+    assert!(sent.len() > 2);
+    assert!(
+        sent.iter()
+            .all(|chunk| HeuristicTokenizer.count(chunk.as_str().unwrap()) <= 3_072)
+    );
+    assert_eq!(response.data.len(), 2);
+    assert_eq!(response.data[0].embedding, [1.0, 0.0]);
+    let norm: f32 = response.data[1]
+        .embedding
+        .iter()
+        .map(|v| v * v)
+        .sum::<f32>()
+        .sqrt();
+    assert!((norm - 1.0).abs() < 1e-5, "norm was {}", norm);
+}
+
+#[tokio::test]
+async fn truncate_policy_sends_one_input_per_request_input() {
+    let server = MockServer::start(alternating_reply).await;
+    let response = server
+        .client(ApiProvider::ZhipuAI)
+        .embeddings(&oversized_request(OversizePolicy::Truncate))
+        .await
+        .unwrap();
+
+    let sent = server.last_request().json();
+    let sent = sent["input"].as_array().unwrap();
+    assert_eq!(sent.len(), 2);
+    assert!(HeuristicTokenizer.count(sent[1].as_str().unwrap()) <= 3_072);
+    assert_eq!(response.data.len(), 2);
+}
+
+#[tokio::test]
+async fn error_policy_rejects_oversized_input_without_sending() {
+    let server = MockServer::start(alternating_reply).await;
+    let result = server
+        .client(ApiProvider::ZhipuAI)
+        .embeddings(&oversized_request(OversizePolicy::Error))
+        .await;
+
+    assert!(matches!(result, Err(LlmHubError::ConfigError(_))));
+    assert!(server.requests().is_empty());
+}
+
+#[tokio::test]
+async fn embeddings_reject_chat_models_without_sending() {
+    let server = MockServer::start(alternating_reply).await;
+    let request = EmbeddingRequest::new(Model::ChatGLM(CHATGLM::Glm4Flash), vec!["hi".to_string()]);
+
+    let result = server
+        .client(ApiProvider::ZhipuAI)
+        .embeddings(&request)
+        .await;
+
+    assert!(matches!(result, Err(LlmHubError::ProviderError(_))));
+    assert!(server.requests().is_empty());
+}
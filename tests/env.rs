@@ -0,0 +1,37 @@
+//! Kept in its own test binary because it mutates the process environment.
+
+use llmhub::api::config::ProviderConfig;
+use llmhub::api::providers::ApiProvider;
+
+#[test]
+fn env_vars_override_an_in_memory_config() {
+    // SAFETY: this binary runs no other tests, so nothing reads the
+    // environment concurrently.
+    unsafe {
+        std::env::set_var("VOLCENGINE_API_KEY", "key-from-env");
+        std::env::set_var("VOLCENGINE_API_BASE", "https://gateway.example/v3");
+        std::env::set_var("TENCENT_API_KEY", "tencent-from-env");
+        std::env::remove_var("TENCENT_API_BASE");
+    }
+    let mut configs = vec![ProviderConfig::new(
+        ApiProvider::Volcengine,
+        None,
+        Some("key-from-code".to_string()),
+    )];
+
+    ProviderConfig::merge_env(&mut configs);
+
+    let volcengine =
+        ProviderConfig::get_provider_config(&configs, ApiProvider::Volcengine).unwrap();
+    assert_eq!(volcengine.api_key.as_deref(), Some("key-from-env"));
+    assert_eq!(
+        volcengine.api_base_url.as_deref(),
+        Some("https://gateway.example/v3")
+    );
+    let tencent = ProviderConfig::get_provider_config(&configs, ApiProvider::Tencent).unwrap();
+    assert_eq!(tencent.api_key.as_deref(), Some("tencent-from-env"));
+    assert_eq!(
+        tencent.api_base_url.as_deref(),
+        Some(ApiProvider::Tencent.base_url())
+    );
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::{MockServer, Reply};
+use futures::StreamExt;
+use llmhub::api::message::Message;
+use llmhub::api::providers::ApiProvider;
+use llmhub::api::request::ApiRequest;
+use llmhub::models::models::{CHATGPT, Model};
+use llmhub::utils::error::LlmHubError;
+use serde_json::json;
+
+fn request() -> ApiRequest {
+    ApiRequest::new(Model::ChatGPT(CHATGPT::V4oMini), None).add_message(Message::user("hi"))
+}
+
+fn quota_exhausted() -> Reply {
+    Reply::status(
+        429,
+        json!({
+            "error": {
+                "message": "You exceeded your current quota.",
+                "type": "insufficient_quota",
+                "code": "insufficient_quota"
+            }
+        }),
+    )
+}
+
+fn rate_limited() -> Reply {
+    Reply::status(
+        429,
+        json!({
+            "error": {
+                "message": "Rate limit reached for requests",
+                "type": "requests",
+                "code": "rate_limit_exceeded"
+            }
+        }),
+    )
+    .with_header("retry-after", "7")
+}
+
+#[tokio::test]
+async fn a_rate_limit_429_carries_the_advised_wait() {
+    let server = MockServer::start(|_| rate_limited()).await;
+    let client = server.client(ApiProvider::OpenAI);
+
+    let result = client.chat(&request()).await;
+    assert!(matches!(result, Err(LlmHubError::RateLimitError(7))));
+
+    let first = client
+        .chat_stream(&request())
+        .unwrap()
+        .next()
+        .await
+        .unwrap();
+    assert!(matches!(first, Err(LlmHubError::RateLimitError(7))));
+}
+
+#[tokio::test]
+async fn a_quota_429_keeps_the_error_details() {
+    let server = MockServer::start(|_| quota_exhausted()).await;
+    let client = server.client(ApiProvider::OpenAI);
+
+    let results = [
+        client.chat(&request()).await.map(|_| ()),
+        client
+            .chat_stream(&request())
+            .unwrap()
+            .next()
+            .await
+            .unwrap()
+            .map(|_| ()),
+    ];
+    for result in results {
+        match result {
+            Err(LlmHubError::ApiErrorResponse(details)) => {
+                assert_eq!(details.status, 429);
+                assert_eq!(details.code.as_deref(), Some("insufficient_quota"));
+                assert!(details.is_quota_exhausted());
+            }
+            other => panic!("expected ApiErrorResponse, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Locks down the exact request bodies sent to OpenAI-compatible providers
+//! and to Anthropic against checked-in JSON.
+
+use llmhub::api::anthropic;
+use llmhub::api::message::{Content, ContentPart, Message};
+use llmhub::api::request::{ApiRequest, RequestOptions};
+use llmhub::api::response::{ToolCall, ToolCallFunction};
+use llmhub::api::tool::Tool;
+use llmhub::models::models::{CHATGPT, CLAUDE, Model};
+use serde_json::{Value, json};
+
+fn request(model: Model) -> ApiRequest {
+    let options = RequestOptions::builder()
+        .temperature(0.5)
+        .top_p(0.75)
+        .max_tokens(256)
+        .stop("END")
+        .stop("STOP")
+        .user("user-42")
+        .build()
+        .unwrap();
+    let mut image = Message::user("");
+    image.content = Some(Content::Parts(vec![
+        ContentPart::text("What is in these images?"),
+        ContentPart::image_url("https://example.com/cat.png"),
+        ContentPart::image_base64("image/png", "iVBORw0KGgo="),
+    ]));
+    let weather = Tool::function(
+        "get_weather",
+        "Get the current weather for a city",
+        json!({
+            "type": "object",
+            "properties": { "city": { "type": "string" } },
+            "required": ["city"]
+        }),
+    );
+    let call = ToolCall {
+        index: None,
+        id: Some("call_1".to_string()),
+        tool_type: Some("function".to_string()),
+        function: Some(ToolCallFunction {
+            name: Some("get_weather".to_string()),
+            arguments: Some(r#"{"city":"Paris"}"#.to_string()),
+        }),
+    };
+
+    let mut request = ApiRequest::new(model, None)
+        .with_options(options)
+        .add_message(Message::system("You are helpful."))
+        .add_message(image)
+        .add_message(Message::assistant_with_tools(vec![call]))
+        .add_message(Message::tool("Sunny, 21C", "call_1"))
+        .with_tools(vec![weather]);
+    request.options.tool_choice = Some(json!("auto"));
+    request
+}
+
+fn assert_golden(actual: Value, expected: &str) {
+    let expected: Value = serde_json::from_str(expected).unwrap();
+    assert_eq!(
+        actual,
+        expected,
+        "body changed; actual:\n{}",
+        serde_json::to_string_pretty(&actual).unwrap()
+    );
+}
+
+#[test]
+fn openai_request_body() {
+    let body = serde_json::to_value(request(Model::ChatGPT(CHATGPT::V4o))).unwrap();
+    assert_golden(body, include_str!("golden/openai_request.json"));
+}
+
+#[test]
+fn anthropic_request_body() {
+    let body = anthropic::request_body(&request(Model::Claude(CLAUDE::Sonnet3_7)));
+    assert_golden(body, include_str!("golden/anthropic_request.json"));
+}
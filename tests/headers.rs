@@ -0,0 +1,76 @@
+mod common;
+
+use common::{MockServer, Reply, chat_completion, content_chunk};
+use futures::StreamExt;
+use llmhub::api::audio::{SpeechRequest, TranscriptionRequest};
+use llmhub::api::embedding::EmbeddingRequest;
+use llmhub::api::message::Message;
+use llmhub::api::providers::ApiProvider;
+use llmhub::api::request::ApiRequest;
+use llmhub::models::models::{CHATGLM, CHATGPT, Model};
+use serde_json::json;
+
+#[tokio::test]
+async fn headers_match_the_endpoint_type() {
+    let server = MockServer::start(|recorded| match recorded.path.as_str() {
+        "/chat/completions" if recorded.json()["stream"] == json!(true) => {
+            Reply::sse(&[&content_chunk("hi"), "[DONE]"])
+        }
+        "/chat/completions" => Reply::json(chat_completion("hi")),
+        "/audio/speech" => Reply::Full {
+            status: 200,
+            headers: vec![("content-type".into(), "audio/mpeg".into())],
+            body: b"ID3".to_vec(),
+        },
+        "/embeddings" => Reply::json(json!({
+            "data": [{ "index": 0, "embedding": [0.5] }],
+            "model": "embedding-3"
+        })),
+        _ => Reply::json(json!({ "text": "hi" })),
+    })
+    .await;
+    let client = server.client(ApiProvider::OpenAI);
+    let chat =
+        ApiRequest::new(Model::ChatGPT(CHATGPT::V4oMini), None).add_message(Message::user("hi"));
+
+    client.chat(&chat).await.unwrap();
+    let sent = server.last_request();
+    assert_eq!(sent.header("accept"), Some("application/json"));
+    assert_eq!(sent.header("content-type"), Some("application/json"));
+
+    let _: Vec<_> = client.chat_stream(&chat).unwrap().collect().await;
+    let sent = server.last_request();
+    assert_eq!(sent.header("accept"), Some("text/event-stream"));
+    assert_eq!(sent.header("content-type"), Some("application/json"));
+
+    let embedding = EmbeddingRequest::new(
+        Model::ChatGLM(CHATGLM::GlmEmbedding3),
+        vec!["hi".to_string()],
+    );
+    server
+        .client(ApiProvider::ZhipuAI)
+        .embeddings(&embedding)
+        .await
+        .unwrap();
+    assert_eq!(
+        server.last_request().header("accept"),
+        Some("application/json")
+    );
+
+    let speech = SpeechRequest::new(ApiProvider::OpenAI, "tts-1", "hi", "alloy");
+    client.speech(&speech).await.unwrap();
+    let sent = server.last_request();
+    assert_eq!(sent.header("accept"), Some("audio/*"));
+    assert_eq!(sent.header("content-type"), Some("application/json"));
+
+    let transcription =
+        TranscriptionRequest::new(ApiProvider::OpenAI, "whisper-1", &b"RIFF"[..], "a.wav");
+    client.transcribe(&transcription).await.unwrap();
+    let sent = server.last_request();
+    assert_eq!(sent.header("accept"), Some("application/json"));
+    assert!(
+        sent.header("content-type")
+            .unwrap()
+            .starts_with("multipart/form-data; boundary=")
+    );
+}
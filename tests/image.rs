@@ -0,0 +1,56 @@
+mod common;
+
+use common::MockServer;
+use llmhub::api::image::{ImageRequest, ImageResponseFormat};
+use llmhub::api::providers::ApiProvider;
+use llmhub::models::models::{CHATGLM, DEEPSEEK, Model};
+use llmhub::utils::error::LlmHubError;
+use serde_json::json;
+
+#[tokio::test]
+async fn generate_image_posts_the_request_and_parses_the_images() {
+    let server = MockServer::json(json!({
+        "created": 1,
+        "data": [{ "url": "https://example.com/cat.png", "revised_prompt": "a cat" }]
+    }))
+    .await;
+    let request = ImageRequest::new(Model::ChatGLM(CHATGLM::CogView4), "a cat")
+        .with_size("1024x1024")
+        .with_response_format(ImageResponseFormat::Url);
+
+    let response = server
+        .client(ApiProvider::ZhipuAI)
+        .generate_image(&request)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.data[0].url.as_deref(),
+        Some("https://example.com/cat.png")
+    );
+    let sent = server.last_request();
+    assert_eq!(sent.path, "/images/generations");
+    assert_eq!(
+        sent.json(),
+        json!({
+            "model": "cogview-4",
+            "prompt": "a cat",
+            "size": "1024x1024",
+            "response_format": "url"
+        })
+    );
+}
+
+#[tokio::test]
+async fn generate_image_rejects_providers_without_image_support() {
+    let server = MockServer::json(json!({ "data": [] })).await;
+    let request = ImageRequest::new(Model::Deepseek(DEEPSEEK::V3Official), "a cat");
+
+    let result = server
+        .client(ApiProvider::Deepseek)
+        .generate_image(&request)
+        .await;
+
+    assert!(matches!(result, Err(LlmHubError::ProviderError(_))));
+    assert!(server.requests().is_empty());
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use common::{MockServer, Reply};
+use llmhub::api::client::list_all_models;
+use llmhub::api::config::ProviderConfig;
+use llmhub::api::providers::ApiProvider;
+use serde_json::json;
+
+fn config(provider: ApiProvider, server: &MockServer) -> ProviderConfig {
+    ProviderConfig {
+        api_provider: provider,
+        api_base_url: Some(server.url.clone()),
+        api_key: Some("test-key".to_string()),
+        extra_headers: None,
+        requests_per_minute: None,
+    }
+}
+
+#[tokio::test]
+async fn list_all_models_keeps_partial_results_when_a_provider_fails() {
+    let openai = MockServer::json(json!({
+        "object": "list",
+        "data": [
+            { "id": "gpt-4o", "object": "model", "owned_by": "openai" },
+            { "id": "gpt-4o-mini", "object": "model", "owned_by": "openai" }
+        ]
+    }))
+    .await;
+    let deepseek =
+        MockServer::start(|_| Reply::status(500, json!({ "error": { "message": "unavailable" } })))
+            .await;
+
+    let catalog = list_all_models(&[
+        config(ApiProvider::OpenAI, &openai),
+        config(ApiProvider::Deepseek, &deepseek),
+    ])
+    .await;
+
+    let ids: Vec<_> = catalog
+        .models
+        .iter()
+        .map(|(provider, model)| (*provider, model.id.as_str()))
+        .collect();
+    assert_eq!(
+        ids,
+        [
+            (ApiProvider::OpenAI, "gpt-4o"),
+            (ApiProvider::OpenAI, "gpt-4o-mini")
+        ]
+    );
+    assert_eq!(catalog.errors.len(), 1);
+    assert_eq!(catalog.errors[0].0, ApiProvider::Deepseek);
+}
+
+#[tokio::test]
+async fn list_models_sends_an_authenticated_get_and_parses_model_info() {
+    let server = MockServer::json(json!({
+        "data": [
+            { "id": "gpt-4o", "object": "model", "created": 1715367049, "owned_by": "system" },
+            { "id": "custom-model" }
+        ]
+    }))
+    .await;
+
+    let models = server
+        .client(ApiProvider::OpenAI)
+        .list_models(ApiProvider::OpenAI)
+        .await
+        .unwrap();
+
+    let sent = server.last_request();
+    assert_eq!(sent.method, "GET");
+    assert_eq!(sent.path, "/models");
+    assert_eq!(sent.header("authorization"), Some("Bearer test-key"));
+    assert!(sent.body.is_empty());
+    assert_eq!(models.len(), 2);
+    assert_eq!(models[0].object, "model");
+    assert_eq!(models[0].created, Some(1715367049));
+    assert_eq!(models[0].owned_by.as_deref(), Some("system"));
+    assert_eq!(models[1].id, "custom-model");
+    assert_eq!(models[1].object, "");
+    assert_eq!(models[1].owned_by, None);
+}
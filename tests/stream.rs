@@ -0,0 +1,204 @@
+mod common;
+
+use common::{MockServer, Reply, content_chunk, stream_chunk};
+use futures::StreamExt;
+use llmhub::api::client::Client;
+use llmhub::api::message::Message;
+use llmhub::api::providers::ApiProvider;
+use llmhub::api::request::ApiRequest;
+use llmhub::api::response::{FinishReason, StreamChunk};
+use llmhub::api::stream::StreamAccumulator;
+use llmhub::models::models::{CHATGPT, Model};
+use llmhub::utils::error::LlmHubError;
+use serde_json::json;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+fn request() -> ApiRequest {
+    ApiRequest::new(Model::ChatGPT(CHATGPT::V4oMini), None).add_message(Message::user("hi"))
+}
+
+async fn stream_contents(server: &MockServer) -> Vec<String> {
+    let chunks: Vec<StreamChunk> = server
+        .client(ApiProvider::OpenAI)
+        .chat_stream(&request())
+        .unwrap()
+        .map(|chunk| chunk.unwrap())
+        .collect()
+        .await;
+    chunks
+        .iter()
+        .filter_map(|chunk| chunk.choices[0].delta.content.clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn accepts_data_prefix_with_and_without_space_and_a_bom() {
+    let spaced = format!("data: {}\n\n", content_chunk("a"));
+    let tight = format!("data:{}\n\n", content_chunk("b"));
+    let bom = format!("data: \u{feff}{}\n\n", content_chunk("c"));
+    let server =
+        MockServer::start(move |_| Reply::raw_stream(&[&spaced, &tight, &bom, "data: [DONE]\n\n"]))
+            .await;
+
+    assert_eq!(stream_contents(&server).await, ["a", "b", "c"]);
+}
+
+#[tokio::test]
+async fn skips_interleaved_heartbeats() {
+    let first = content_chunk("Hel");
+    let second = content_chunk("lo");
+    let server = MockServer::start(move |_| {
+        Reply::sse(&[&first, "{}", "", &second, r#"{"ping":1}"#, "[DONE]"])
+    })
+    .await;
+
+    assert_eq!(stream_contents(&server).await, ["Hel", "lo"]);
+}
+
+#[tokio::test]
+async fn tool_calls_finish_is_captured_before_done() {
+    let call = stream_chunk(
+        json!({ "tool_calls": [{
+            "index": 0,
+            "id": "call_1",
+            "type": "function",
+            "function": { "name": "get_weather", "arguments": "{\"city\":\"Paris\"}" }
+        }] }),
+        None,
+    );
+    let finish = stream_chunk(json!({}), Some("tool_calls"));
+    let server = MockServer::start(move |_| {
+        Reply::hanging_stream(&[
+            &format!("data: {}\n\n", call),
+            &format!("data: {}\n\n", finish),
+        ])
+    })
+    .await;
+
+    let mut stream = server
+        .client(ApiProvider::OpenAI)
+        .chat_stream(&request())
+        .unwrap();
+    let mut accumulator = StreamAccumulator::new();
+    accumulator.push(&stream.next().await.unwrap().unwrap());
+    assert_eq!(accumulator.finish_reason(0), None);
+    accumulator.push(&stream.next().await.unwrap().unwrap());
+
+    assert_eq!(accumulator.finish_reason(0), Some(FinishReason::ToolCalls));
+    assert_eq!(accumulator.tool_calls(0).len(), 1);
+}
+
+#[tokio::test]
+async fn retries_a_stream_that_ends_immediately() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let server = {
+        let attempts = attempts.clone();
+        let chunk = content_chunk("recovered");
+        let finish = stream_chunk(json!({}), Some("stop"));
+        MockServer::start(move |_| match attempts.fetch_add(1, Ordering::SeqCst) {
+            0 => Reply::sse(&["[DONE]"]),
+            _ => Reply::sse(&[&chunk, &finish, "[DONE]"]),
+        })
+        .await
+    };
+    let client = server.client(ApiProvider::OpenAI);
+
+    let response = client.chat_stream_collect(&request(), 1).await.unwrap();
+    assert_eq!(response.content(0), Some("recovered"));
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+    attempts.store(0, Ordering::SeqCst);
+    let response = client.chat_stream_collect(&request(), 0).await.unwrap();
+    assert!(response.is_empty());
+}
+
+#[tokio::test]
+async fn does_not_retry_a_stream_that_only_carried_tool_calls() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let server = {
+        let attempts = attempts.clone();
+        let call = stream_chunk(
+            json!({ "tool_calls": [{
+                "index": 0,
+                "id": "call_1",
+                "type": "function",
+                "function": { "name": "get_weather", "arguments": "{\"city\":" }
+            }] }),
+            None,
+        );
+        MockServer::start(move |_| {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Reply::sse(&[&call, "[DONE]"])
+        })
+        .await
+    };
+    let client = server.client(ApiProvider::OpenAI);
+
+    let response = client.chat_stream_collect(&request(), 1).await.unwrap();
+    assert!(!response.is_empty());
+    assert_eq!(response.tool_calls(0).len(), 1);
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn dropping_a_stream_mid_flight_closes_the_connection() {
+    let first = format!("data: {}\n\n", content_chunk("partial"));
+    let server = MockServer::start(move |_| Reply::hanging_stream(&[&first])).await;
+    let mut stream = server
+        .client(ApiProvider::OpenAI)
+        .chat_stream(&request())
+        .unwrap();
+
+    let chunk = stream.next().await.unwrap().unwrap();
+    assert_eq!(chunk.choices[0].delta.content.as_deref(), Some("partial"));
+    drop(stream);
+
+    assert!(server.wait_for_disconnect(Duration::from_secs(5)).await);
+}
+
+#[tokio::test]
+async fn a_stalled_stream_fails_after_the_read_timeout() {
+    let first = format!("data: {}\n\n", content_chunk("partial"));
+    let server = MockServer::start(move |_| Reply::hanging_stream(&[&first])).await;
+    let client = Client::builder()
+        .api_key("test-key")
+        .read_timeout(Duration::from_millis(200))
+        .build()
+        .unwrap()
+        .without_rate_limit()
+        .with_base_url(ApiProvider::OpenAI, server.url.clone());
+
+    let results: Vec<_> = tokio::time::timeout(
+        Duration::from_secs(5),
+        client.chat_stream(&request()).unwrap().collect::<Vec<_>>(),
+    )
+    .await
+    .expect("the stream ends instead of hanging");
+
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    assert!(matches!(results[1], Err(LlmHubError::TimeoutError(_))));
+}
+
+#[tokio::test]
+async fn cancelling_ends_a_never_ending_stream_promptly() {
+    let first = format!("data: {}\n\n", content_chunk("partial"));
+    let server = MockServer::start(move |_| Reply::hanging_stream(&[&first])).await;
+    let cancel = CancellationToken::new();
+    let mut stream = server
+        .client(ApiProvider::OpenAI)
+        .chat_stream_with_cancel(&request(), cancel.clone())
+        .unwrap();
+
+    let chunk = stream.next().await.unwrap().unwrap();
+    assert_eq!(chunk.choices[0].delta.content.as_deref(), Some("partial"));
+    cancel.cancel();
+
+    let end = tokio::time::timeout(Duration::from_secs(1), stream.next()).await;
+    assert!(matches!(end, Ok(None)), "the stream ends once cancelled");
+    // The response is dropped even while the caller still holds the stream.
+    assert!(server.wait_for_disconnect(Duration::from_secs(5)).await);
+}